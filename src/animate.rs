@@ -0,0 +1,80 @@
+// Plays the grid simulations step by step in the terminal.
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crate::utils::*;
+
+// Maps a rendered map character to an ANSI SGR color (like "1;33"), or None to leave it plain.
+pub type Palette = fn(char) -> Option<&'static str>;
+
+pub struct Animation {
+    frame_time: Duration,
+    last_frame: Option<Instant>,
+    max_cols: usize,
+    max_rows: usize,
+}
+
+fn env_size(name: &str, default: usize) -> usize {
+    std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+impl Animation {
+    pub fn new(fps: f32) -> Self {
+        Animation{
+            frame_time: Duration::from_secs_f32(1.0 / fps.max(0.1)),
+            last_frame: None,
+            // Leaves room for the title.
+            max_cols: env_size("COLUMNS", 160),
+            max_rows: env_size("LINES", 50).saturating_sub(2),
+        }
+    }
+
+    // Draws a map over the previous frame, waiting out the rest of the frame time first.
+    pub fn frame(&mut self, title: &str, map: &Grid<u8>, palette: Palette) {
+        let mut out = String::with_capacity(map.rows * (map.cols + 1) * 4);
+        out.push_str("\x1b[H\x1b[2J");
+        out.push_str(title);
+        if map.rows > self.max_rows || map.cols > self.max_cols {
+            out.push_str(&format!("  (showing {}x{} of {}x{})",
+                map.cols.min(self.max_cols), map.rows.min(self.max_rows), map.cols, map.rows));
+        }
+        out.push('\n');
+
+        for line in map.fmt_map().lines().take(self.max_rows) {
+            let mut color = None;
+            for ch in line.chars().take(self.max_cols) {
+                let next_color = palette(ch);
+                if next_color != color {
+                    out.push_str(&format!("\x1b[{}m", next_color.unwrap_or("0")));
+                    color = next_color;
+                }
+                out.push(ch);
+            }
+            out.push_str("\x1b[0m\n");
+        }
+
+        if let Some(last) = self.last_frame {
+            let shown_for = last.elapsed();
+            if shown_for < self.frame_time {
+                std::thread::sleep(self.frame_time - shown_for);
+            }
+        }
+        let mut stdout = std::io::stdout();
+        stdout.write_all(out.as_bytes()).unwrap();
+        stdout.flush().unwrap();
+        self.last_frame = Some(Instant::now());
+    }
+}
+
+pub fn animate(day: usize, fps: f32) {
+    let input_str = std::fs::read_to_string(format!("inputs/input{:02}.txt", day)).expect("Cannot open file");
+    let mut anim = Animation::new(fps);
+    match day {
+        11 => crate::day11::animate(&input_str, &mut anim),
+        13 => crate::day13::animate(&input_str, &mut anim),
+        20 => crate::day20::animate(&input_str, &mut anim),
+        25 => crate::day25::animate(&input_str, &mut anim),
+        _ => println!("Day {} has nothing to animate (try 11, 13, 20 or 25)", day),
+    }
+}
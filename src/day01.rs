@@ -1,44 +1,201 @@
-use std::io::{BufRead, BufReader};
 use std::fs::File;
+use std::io::{self, BufRead, BufReader};
 
+use clap::Args;
 
-pub fn day01(_test_mode: bool, print: bool) {
-    let reader = BufReader::new(File::open("inputs/input01.txt").expect("Cannot open file"));
-    let mut inputs = Vec::<i32>::with_capacity(100);
-    for line in reader.lines() {
-        let x: i32 = line.unwrap().parse().unwrap();
-        inputs.push(x);
+use crate::utils::*;
+
+// Counts how often the sum of a sliding window of depth readings goes up.
+//
+// Neighbouring windows share all but their end readings, so only the reading entering a
+// window is compared with the one leaving it, and only the last `window` readings are kept.
+pub struct SonarSweep {
+    // The last `window` readings, as a ring, with `next` the oldest.
+    recent: Vec<i32>,
+    next: usize,
+    seen: usize,
+    pub increases: usize,
+    // Indices of the readings that end a window deeper than the one before, if recording.
+    pub positions: Option<Vec<usize>>,
+}
+
+impl SonarSweep {
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "A window needs at least one reading");
+        SonarSweep{recent: vec![0; window], next: 0, seen: 0, increases: 0, positions: None}
+    }
+
+    pub fn recording_positions(mut self) -> Self {
+        self.positions = Some(Vec::new());
+        self
     }
 
-    let mut cnt = 0;
-    for i in 1..inputs.len() {
-        if inputs[i] > inputs[i - 1] {
-            cnt += 1;
+    pub fn window(&self) -> usize {
+        self.recent.len()
+    }
+
+    // Whether the window ending with this reading is deeper than the one before.
+    #[inline]
+    pub fn push(&mut self, reading: i32) -> bool {
+        let increased = self.seen >= self.recent.len() && reading > self.recent[self.next];
+        if increased {
+            self.increases += 1;
+            if let Some(positions) = &mut self.positions {
+                positions.push(self.seen);
+            }
         }
+        self.recent[self.next] = reading;
+        self.next = if self.next + 1 == self.recent.len() { 0 } else { self.next + 1 };
+        self.seen += 1;
+        increased
     }
-    // for (a, b) in inputs.iter().zip(inputs.iter().skip(1)) {
-    //     if b > a {
-    //         cnt += 1;
-    //     }
-    // }
-    
+}
 
-    if print {
-        println!("Day 1.  Part 1: {}", cnt);
+// The reading on one line, or None for a blank line. Surrounding whitespace, including a
+// trailing \r, is ignored.
+fn parse_reading(line: &[u8], line_num: usize) -> io::Result<Option<i32>> {
+    let mut scanner = ByteScanner::from_bytes(line);
+    scanner.skip_whitespace();
+    if scanner.at_end() {
+        return Ok(None);
+    }
+    scanner.int::<i32>()
+        .filter(|_| { scanner.skip_whitespace(); scanner.at_end() })
+        .map(Some)
+        .ok_or_else(|| bad_reading(line, line_num))
+}
+
+#[cold]
+fn bad_reading(line: &[u8], line_num: usize) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData,
+        format!("Bad reading on line {}: {:?}", line_num, String::from_utf8_lossy(line).trim()))
+}
+
+// Feeds readings, one per line, through every sweep without holding on to them. Blank
+// lines are skipped.
+pub fn sweep_readings(mut reader: impl BufRead, sweeps: &mut [SonarSweep]) -> io::Result<()> {
+    let mut line = Vec::with_capacity(16);
+    let mut line_num = 0;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        line_num += 1;
+        if let Some(reading) = parse_reading(&line, line_num)? {
+            for sweep in sweeps.iter_mut() {
+                sweep.push(reading);
+            }
+        }
+    }
+}
+
+// Like sweep_readings, but straight from the input without copying each line.
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let mut sweeps = [SonarSweep::new(1), SonarSweep::new(3)];
+    for (i, line) in input_str.as_bytes().split(|b| *b == b'\n').enumerate() {
+        if let Some(reading) = parse_reading(line, i + 1).unwrap_or_else(|err| panic!("{}", err)) {
+            for sweep in sweeps.iter_mut() {
+                sweep.push(reading);
+            }
+        }
+    }
+    (sweeps[0].increases, sweeps[1].increases)
+}
+
+#[derive(Args)]
+pub struct SonarOpts {
+    /// File of depth readings, one per line (defaults to stdin)
+    file: Option<String>,
+    /// Readings summed per window; repeat for several (defaults to 1 and 3)
+    #[clap(short, long)]
+    window: Vec<usize>,
+    /// Also lists the index of each reading that ends a deeper window
+    #[clap(long)]
+    positions: bool,
+}
+
+pub fn sonar(opts: &SonarOpts) {
+    let windows = if opts.window.is_empty() { vec![1, 3] } else { opts.window.clone() };
+    if windows.contains(&0) {
+        eprintln!("Windows need at least one reading");
+        std::process::exit(1);
+    }
+    let mut sweeps: Vec<SonarSweep> = windows.iter().map(|&window| {
+        let sweep = SonarSweep::new(window);
+        if opts.positions { sweep.recording_positions() } else { sweep }
+    }).collect();
+
+    let result = match &opts.file {
+        Some(path) => File::open(path).and_then(|file| sweep_readings(BufReader::new(file), &mut sweeps)),
+        None => sweep_readings(io::stdin().lock(), &mut sweeps),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
     }
-    assert_eq!(cnt, 1215);
 
-    let mut cnt = 0;
-    for i in 0..(inputs.len() - 2 - 1) {
-        // Comparing windows
-        //  i, i+1, i+2
-        //     i+1, i+2, i+3
-        if inputs[i + 3] > inputs[i] {
-            cnt += 1;
+    for sweep in &sweeps {
+        println!("Window {}: {} increases", sweep.window(), sweep.increases);
+        if let Some(positions) = &sweep.positions {
+            for pos in positions {
+                println!("{}", pos);
+            }
         }
     }
+}
+
+pub fn day01(_test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input01.txt";
+    let input_str = std::fs::read_to_string(INPUT).expect("Cannot open file");
+
+    let (part1, part2) = solve(&input_str);
+    if print {
+        println!("Day 1.  Part 1: {}", part1);
+    }
+    assert_eq!(part1, 1215);
+
     if print {
-        println!("Day 1.  Part 2: {}", cnt);
+        println!("Day 1.  Part 2: {}", part2);
+    }
+    assert_eq!(part2, 1150);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sweep(input: &str) -> io::Result<usize> {
+        let mut sweeps = [SonarSweep::new(1)];
+        sweep_readings(input.as_bytes(), &mut sweeps)?;
+        Ok(sweeps[0].increases)
+    }
+
+    #[test]
+    fn sweeps() {
+        assert_eq!(sweep("199\n200\n208\n\n210\n200\r\n-7\n").unwrap(), 3);
+        assert_eq!(sweep("-2147483648\n2147483647\n").unwrap(), 1);
+    }
+
+    #[test]
+    fn solves_crlf_input() {
+        let input = "199\r\n200\r\n208\r\n210\r\n200\r\n207\r\n240\r\n269\r\n260\r\n263\r\n\r\n";
+        assert_eq!(solve(input), (7, 5));
+        assert_eq!(solve(&input.replace("\r\n", "\n")), (7, 5));
+    }
+
+    #[test]
+    #[should_panic(expected = "Bad reading on line 2: \"2x\"")]
+    fn solve_rejects_a_bad_reading() {
+        solve("1\r\n2x\r\n3\r\n");
+    }
+
+    #[test]
+    fn bad_readings() {
+        for input in ["1\n3000000000\n", "1\n12345678901234567890123\n", "1\n-2147483649\n", "1\n2 3\n", "1\nx\n"] {
+            let err = sweep(input).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().starts_with("Bad reading on line 2"), "{}", err);
+        }
     }
-    assert_eq!(cnt, 1150);
 }
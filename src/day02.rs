@@ -1,53 +1,263 @@
-use std::io::{BufRead, BufReader};
-use std::fs::File;
+use std::str::FromStr;
 
+use clap::Args;
 
-pub fn day02(_test_mode: bool, print: bool) {
-    let reader = BufReader::new(File::open("inputs/input02.txt").expect("Cannot open file"));
-    enum Dir {
-        Forward, Up, Down
-    }
-    let mut inputs = Vec::<(Dir, i32)>::with_capacity(100);
-    for line in reader.lines() {
-        let saved_line = line.unwrap();
-        let (dir_str, dist_str) = saved_line.split_once(" ").unwrap();
-        let dir = match dir_str.chars().next().unwrap() {
-            'f' => Dir::Forward,
-            'u' => Dir::Up,
-            'd' => Dir::Down,
-            _ => panic!("Bad direction"),
-        };
-        let dist: i32 = dist_str.parse().unwrap();
-        inputs.push((dir, dist));
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Forward(i32),
+    Up(i32),
+    Down(i32),
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (word, dist_str) = s.split_once(' ').ok_or_else(|| format!("Bad command: {:?}", s))?;
+        let dist = dist_str.trim_end().parse().map_err(|_| format!("Bad distance: {:?}", dist_str))?;
+        match word {
+            "forward" => Ok(Command::Forward(dist)),
+            "up" => Ok(Command::Up(dist)),
+            "down" => Ok(Command::Down(dist)),
+            _ => Err(format!("Bad direction: {:?}", word)),
+        }
     }
+}
 
-    let mut x = 0;
-    let mut depth = 0; 
-    for (dir, dist) in &inputs {
-        match dir {
-            Dir::Forward => x += dist,
-            Dir::Up => depth -= dist,
-            Dir::Down => depth += dist,
+// One command per line. Blank lines are skipped.
+pub fn parse_course(input_str: &str) -> Result<Vec<Command>, String> {
+    let mut course = Vec::with_capacity(1000);
+    for (idx, line) in input_str.lines().enumerate() {
+        if !line.is_empty() {
+            course.push(line.parse().map_err(|err| format!("Line {}: {}", idx + 1, err))?);
         }
     }
+    Ok(course)
+}
 
-    if print {
-        println!("Part 1: {}", x * depth);
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub x: i64,
+    pub depth: i64,
+}
+
+impl Position {
+    // The puzzle answer. Wide enough that it can't overflow.
+    pub fn product(self) -> i128 {
+        self.x as i128 * self.depth as i128
+    }
+}
+
+// What a command does to the submarine, or None if the numbers overflow. Closures over
+// (position, command) work too.
+pub trait Steering {
+    fn steer(&mut self, pos: &mut Position, cmd: Command) -> Option<()>;
+}
+
+impl<F: FnMut(&mut Position, Command) -> Option<()>> Steering for F {
+    fn steer(&mut self, pos: &mut Position, cmd: Command) -> Option<()> {
+        self(pos, cmd)
+    }
+}
+
+// Part 1: up and down change the depth directly.
+pub struct Plain;
+
+impl Steering for Plain {
+    fn steer(&mut self, pos: &mut Position, cmd: Command) -> Option<()> {
+        match cmd {
+            Command::Forward(dist) => pos.x = pos.x.checked_add(dist as i64)?,
+            Command::Up(dist) => pos.depth = pos.depth.checked_sub(dist as i64)?,
+            Command::Down(dist) => pos.depth = pos.depth.checked_add(dist as i64)?,
+        }
+        Some(())
+    }
+}
+
+// Part 2: up and down tilt the aim, and going forward dives along it.
+#[derive(Default)]
+pub struct Aimed {
+    pub aim: i64,
+}
+
+impl Steering for Aimed {
+    fn steer(&mut self, pos: &mut Position, cmd: Command) -> Option<()> {
+        match cmd {
+            Command::Forward(dist) => {
+                let dive = (dist as i64).checked_mul(self.aim)?;
+                *pos = Position{x: pos.x.checked_add(dist as i64)?, depth: pos.depth.checked_add(dive)?};
+            }
+            Command::Up(dist) => self.aim = self.aim.checked_sub(dist as i64)?,
+            Command::Down(dist) => self.aim = self.aim.checked_add(dist as i64)?,
+        }
+        Some(())
+    }
+}
+
+pub struct Submarine<S> {
+    pub steering: S,
+    pos: Position,
+    // Where it was before the first command and after each one, if recording.
+    path: Option<Vec<Position>>,
+}
+
+impl<S: Steering> Submarine<S> {
+    pub fn new(steering: S) -> Self {
+        Submarine{steering, pos: Position::default(), path: None}
+    }
+
+    pub fn recording_path(mut self) -> Self {
+        self.path = Some(vec![self.pos]);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn position(&self) -> Position {
+        self.pos
+    }
+
+    #[cfg(test)]
+    pub fn path(&self) -> Option<&[Position]> {
+        self.path.as_deref()
     }
-    assert_eq!(x * depth, 1670340);
 
-    let mut x = 0;
-    let mut depth = 0;
-    let mut aim = 0;
-    for (dir, dist) in inputs {
-        match dir {
-            Dir::Forward => { x += dist; depth += dist * aim; }
-            Dir::Up => aim -= dist,
-            Dir::Down => aim += dist,
+    // None, leaving the position alone, if the command takes it out of range.
+    pub fn run(&mut self, cmd: Command) -> Option<()> {
+        let mut next = self.pos;
+        self.steering.steer(&mut next, cmd)?;
+        self.pos = next;
+        if let Some(path) = &mut self.path {
+            path.push(self.pos);
         }
+        Some(())
     }
+
+    pub fn run_all<'a>(&mut self, course: impl IntoIterator<Item = &'a Command>) -> Result<Position, String> {
+        for (idx, cmd) in course.into_iter().enumerate() {
+            self.run(*cmd).ok_or_else(|| format!("Command {} ({:?}) overflows", idx + 1, cmd))?;
+        }
+        Ok(self.pos)
+    }
+}
+
+pub fn solve(input_str: &str) -> (i128, i128) {
+    let course = parse_course(input_str).unwrap();
+
+    let part1 = Submarine::new(Plain).run_all(&course).unwrap().product();
+    let part2 = Submarine::new(Aimed::default()).run_all(&course).unwrap().product();
+
+    (part1, part2)
+}
+
+#[derive(Args)]
+pub struct CourseOpts {
+    /// Course files, one command per line
+    #[clap(required = true)]
+    files: Vec<String>,
+    /// Uses the part 1 rules, where up and down change the depth directly
+    #[clap(long)]
+    plain: bool,
+    /// Prints every position along the way
+    #[clap(long)]
+    path: bool,
+}
+
+// Replays each course, and says where the later ones first part ways with the first.
+pub fn course(opts: &CourseOpts) {
+    let mut paths = Vec::with_capacity(opts.files.len());
+    for file in &opts.files {
+        let replayed = std::fs::read_to_string(file).map_err(|err| err.to_string())
+            .and_then(|s| parse_course(&s))
+            .and_then(|course| {
+                let path = if opts.plain {
+                    replay(Submarine::new(Plain), &course)
+                } else {
+                    replay(Submarine::new(Aimed::default()), &course)
+                };
+                Ok((course, path?))
+            });
+        let (course, path) = match replayed {
+            Ok(replayed) => replayed,
+            Err(err) => {
+                eprintln!("{}: {}", file, err);
+                std::process::exit(1);
+            }
+        };
+        let end = path[path.len() - 1];
+        println!("{}: {} commands, ends at x {}, depth {} (product {})",
+            file, course.len(), end.x, end.depth, end.product());
+        if opts.path {
+            for (step, pos) in path.iter().enumerate() {
+                println!("{:6} {:8} {:10}", step, pos.x, pos.depth);
+            }
+        }
+        paths.push(path);
+    }
+
+    for (file, path) in opts.files.iter().zip(&paths).skip(1) {
+        match path.iter().zip(&paths[0]).position(|(a, b)| a != b) {
+            Some(step) => println!("{} parts from {} at step {}", file, opts.files[0], step),
+            None if path.len() == paths[0].len() => println!("{} follows {} exactly", file, opts.files[0]),
+            None => println!("{} matches {} for the first {} steps", file, opts.files[0], path.len().min(paths[0].len()) - 1),
+        }
+    }
+}
+
+fn replay(mut sub: Submarine<impl Steering>, course: &[Command]) -> Result<Vec<Position>, String> {
+    sub = sub.recording_path();
+    sub.run_all(course)?;
+    Ok(sub.path.unwrap())
+}
+
+pub fn day02(_test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input02.txt";
+    let input_str = std::fs::read_to_string(INPUT).expect("Cannot open file");
+
+    let (part1, part2) = solve(&input_str);
     if print {
-        println!("Part 2: {}", x * depth);
+        println!("Part 1: {}", part1);
+    }
+    assert_eq!(part1, 1670340);
+
+    if print {
+        println!("Part 2: {}", part2);
+    }
+    assert_eq!(part2, 1954293920);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn steers() {
+        let course = parse_course("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n").unwrap();
+        assert_eq!(Submarine::new(Plain).run_all(&course), Ok(Position{x: 15, depth: 10}));
+        assert_eq!(Submarine::new(Aimed::default()).run_all(&course), Ok(Position{x: 15, depth: 60}));
+    }
+
+    #[test]
+    fn wide_but_in_range() {
+        // Past i32, which is where this used to wrap.
+        let course = parse_course("forward 2000000000\ndown 5\nforward 2000000000\n").unwrap();
+        assert_eq!(Submarine::new(Aimed::default()).run_all(&course), Ok(Position{x: 4000000000, depth: 10000000000}));
+    }
+
+    #[test]
+    fn product_past_i64() {
+        let end = Position{x: 4000000000000, depth: -5000000000000};
+        assert_eq!(end.product(), -20000000000000000000000000);
+        assert_eq!(Position{x: i64::MAX, depth: i64::MIN}.product(), i64::MAX as i128 * i64::MIN as i128);
+        assert_eq!(solve("forward 3\ndown 4\n"), (12, 0));
+    }
+
+    #[test]
+    fn reports_overflow() {
+        let course = parse_course("down 2000000000\ndown 2000000000\ndown 2000000000\nforward 2000000000\n").unwrap();
+        let mut sub = Submarine::new(Aimed::default()).recording_path();
+        assert_eq!(sub.run_all(&course), Err("Command 4 (Forward(2000000000)) overflows".to_string()));
+        assert_eq!(sub.position(), Position::default());
+        assert_eq!(sub.path().unwrap().len(), 4);
+        assert_eq!(sub.steering.aim, 6000000000);
     }
-    assert_eq!(x * depth, 1954293920);
 }
@@ -1,120 +1,289 @@
-use crate::utils::*;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
 
+use clap::Args;
 
-pub fn day03(_test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input03.txt";
-    let input_str = std::fs::read_to_string(INPUT).unwrap();
+// A bit string of any width, most significant bit first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bits(pub Vec<bool>);
 
-    // Constructs the grid manually so we can stream it in.
-    let mut grid_data = Vec::<u8>::with_capacity(1001 * 12);
-    let mut grid_rows = 0;
-    for ch in input_str.bytes() {
-        match ch {
-            b'0' => grid_data.push(0),
-            b'1' => grid_data.push(1),
-            b'\n' => grid_rows += 1,
-            _ => unreachable!(),
+impl Bits {
+    // None if it's too wide to fit.
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.0.len() > 64 {
+            return None;
         }
+        Some(self.0.iter().fold(0, |acc, bit| (acc << 1) | *bit as u64))
     }
+}
 
-    // Slower, unfortunately. Probably because our capacity guess is better than collect's
-    // let grid_data: Vec<u8> = input_str.bytes()
-    //     .filter_map(|b| match b {
-    //         b'0' => Some(0),
-    //         b'1' => Some(1),
-    //         b'\n' => { grid_rows += 1; None},
-    //         _ => unreachable!(),
-    //     }).collect();
+impl fmt::Display for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for bit in &self.0 {
+            write!(f, "{}", if *bit { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
 
-    let grid = Grid::<u8>{rows: grid_rows, cols: grid_data.len() / grid_rows, data: grid_data};
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    // Ties keep the ones (the oxygen rating).
+    MostCommon,
+    // Ties keep the zeros (the CO2 rating).
+    LeastCommon,
+}
 
+// The reading a bit criterion settles on, and how many were still in the running before
+// each bit was looked at.
+pub struct Rating {
+    pub value: Bits,
+    pub candidates: Vec<usize>,
+}
 
-    let mut cnt_ones = vec![0; grid.cols];
-    for row in 0..grid.rows {
-        for k in 0..grid.cols {
-            if grid[(row, k)] == 1 {
-                cnt_ones[k] += 1;
+// The readings of a diagnostic report, in a binary trie so the bit criteria only walk one
+// path down it rather than refiltering all the readings per bit.
+pub struct Diagnostic {
+    width: usize,
+    // Nodes by index, with the root at 0. Since the root is nobody's child, 0 also means
+    // there's no child that way.
+    children: Vec<[usize; 2]>,
+    // How many readings pass through each node.
+    counts: Vec<usize>,
+    // How many readings have a 1 in each column.
+    ones: Vec<usize>,
+}
+
+impl Diagnostic {
+    pub fn new(width: usize) -> Self {
+        Diagnostic{width, children: vec![[0, 0]], counts: vec![0], ones: vec![0; width]}
+    }
+
+    // One reading per line, all the same width. Blank lines are skipped.
+    pub fn parse(input_str: &str) -> Result<Diagnostic, String> {
+        // As in read(), the first reading sets the width.
+        let width = input_str.lines().map(str::trim_end).find(|line| !line.is_empty()).map_or(0, str::len);
+        let mut report = Diagnostic::new(width);
+        for (idx, line) in input_str.lines().enumerate() {
+            if !line.trim_end().is_empty() {
+                report.insert(line.trim_end()).map_err(|err| format!("Line {}: {}", idx + 1, err))?;
             }
         }
+        Ok(report)
     }
 
-    let mut epsilon = 0;
-    let mut gamma = 0;
-    for k in 0..cnt_ones.len() {
-        gamma <<= 1;
-        epsilon <<= 1;
-        if cnt_ones[k] > grid.rows / 2 {
-            gamma += 1;
-        } else {
-            epsilon += 1;
+    // Streams the readings in, without keeping the text.
+    pub fn read(mut reader: impl BufRead) -> io::Result<Diagnostic> {
+        let mut line = String::new();
+        let mut report: Option<Diagnostic> = None;
+        let mut line_num = 0;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(report.unwrap_or_else(|| Diagnostic::new(0)));
+            }
+            line_num += 1;
+            let reading = line.trim_end();
+            if reading.is_empty() {
+                continue;
+            }
+            report.get_or_insert_with(|| Diagnostic::new(reading.len())).insert(reading)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("Line {}: {}", line_num, err)))?;
         }
     }
-    if print {
-        println!("Part 1: {}  (because {:?})", gamma * epsilon, cnt_ones);
-    }
-    assert_eq!(gamma * epsilon, 3959450);
-
-    // Oxygen
-    let mut keep = vec![true; grid.rows];
-    let mut value: i32 = if cnt_ones[0] > grid.rows / 2 { 1 } else { 0 };
-    for k in 1..cnt_ones.len() {
-        let mut next_ones: usize = 0;
-        let mut next_total: usize = 0;
-        for row in 0..grid.rows {
-            if keep[row] {
-                if grid[(row, k - 1)] == (value & 0b1) as u8 {
-                    // Keep keeping, and count this one
-                    next_ones += grid[(row, k)] as usize;
-                    next_total += 1;
-                } else {
-                    keep[row] = false;
-                }
+
+    pub fn insert(&mut self, reading: &str) -> Result<(), String> {
+        if reading.len() != self.width {
+            return Err(format!("Expected {} bits, found {:?}", self.width, reading));
+        }
+        if let Some(bad) = reading.chars().find(|ch| *ch != '0' && *ch != '1') {
+            return Err(format!("Bad bit {:?} in {:?}", bad, reading));
+        }
+        let mut node = 0;
+        self.counts[0] += 1;
+        for (col, bit) in reading.bytes().enumerate() {
+            let bit = (bit - b'0') as usize;
+            self.ones[col] += bit;
+            if self.children[node][bit] == 0 {
+                self.children[node][bit] = self.children.len();
+                self.children.push([0, 0]);
+                self.counts.push(0);
             }
+            node = self.children[node][bit];
+            self.counts[node] += 1;
         }
+        Ok(())
+    }
 
-        value <<= 1;
-        if next_ones * 2 >= next_total { value += 1; }
-    }
-    let oxygen = value;
-
-
-    // CO2
-    let mut keep = vec![true; grid.rows];
-    let mut value: i32 = if !(cnt_ones[0] > grid.rows / 2) { 1 } else { 0 };
-    for k in 1..cnt_ones.len() {
-        let mut next_ones: usize = 0;
-        let mut next_total: usize = 0;
-        let mut kept_row = 0;
-        for row in 0..grid.rows {
-            if keep[row] {
-                if grid[(row, k - 1)] == (value & 0b1) as u8 {
-                    // Keep keeping, and count this one
-                    next_ones += grid[(row, k)] as usize;
-                    next_total += 1;
-                    kept_row = row;
-                } else {
-                    keep[row] = false;
-                }
-            }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts[0]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Each column's most common bit, with ties going to 0.
+    pub fn gamma(&self) -> Bits {
+        Bits(self.ones.iter().map(|ones| ones * 2 > self.len()).collect())
+    }
+
+    // The complement of gamma.
+    pub fn epsilon(&self) -> Bits {
+        Bits(self.gamma().0.iter().map(|bit| !bit).collect())
+    }
+
+    // Narrows the readings down bit by bit to the ones with the kept bit, until there's only
+    // one left. None if there are no readings.
+    pub fn rating(&self, keep: Keep) -> Option<Rating> {
+        if self.is_empty() {
+            return None;
         }
+        let count = |node: usize| if node == 0 { 0 } else { self.counts[node] };
+        let mut value = Vec::with_capacity(self.width);
+        let mut candidates = Vec::with_capacity(self.width);
+        let mut node = 0;
+        for _ in 0..self.width {
+            candidates.push(self.counts[node]);
+            let [zeros, ones] = self.children[node].map(count);
+            // With only one way to go, that's where the last candidates are.
+            let bit = match keep {
+                _ if zeros == 0 || ones == 0 => ones > 0,
+                Keep::MostCommon => ones >= zeros,
+                Keep::LeastCommon => ones < zeros,
+            };
+            value.push(bit);
+            node = self.children[node][bit as usize];
+        }
+        Some(Rating{value: Bits(value), candidates})
+    }
+}
 
-        // Early exit if there's one value left
-        if next_total == 1 {
-            value = 0;
-            for c in 0..grid.cols {
-                value <<= 1;
-                value += grid[(kept_row, c)] as i32;
-            }
-            break;
+pub fn solve(input_str: &str) -> (u64, u64) {
+    let report = Diagnostic::parse(input_str).unwrap();
+
+    let part1 = report.gamma().to_u64().unwrap() * report.epsilon().to_u64().unwrap();
+
+    let oxygen = report.rating(Keep::MostCommon).unwrap().value.to_u64().unwrap();
+    let co2 = report.rating(Keep::LeastCommon).unwrap().value.to_u64().unwrap();
+    let part2 = oxygen * co2;
+
+    (part1, part2)
+}
+
+#[derive(Args)]
+pub struct DiagnosticOpts {
+    /// Report file, one reading of 0s and 1s per line (defaults to stdin)
+    file: Option<String>,
+    /// Also shows how many readings were left before each bit of the ratings
+    #[clap(long)]
+    candidates: bool,
+}
+
+pub fn diagnostic(opts: &DiagnosticOpts) {
+    let result = match &opts.file {
+        Some(path) => File::open(path).and_then(|file| Diagnostic::read(BufReader::new(file))),
+        None => Diagnostic::read(io::stdin().lock()),
+    };
+    let report = match result {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    println!("{} readings of {} bits", report.len(), report.width());
+    if report.is_empty() {
+        return;
+    }
+
+    // Too-wide values are only shown in binary.
+    let show = |name: &str, bits: &Bits| match bits.to_u64() {
+        Some(value) => println!("{:<8} {} ({})", name, bits, value),
+        None => println!("{:<8} {}", name, bits),
+    };
+    show("Gamma", &report.gamma());
+    show("Epsilon", &report.epsilon());
+    for (name, keep) in [("Oxygen", Keep::MostCommon), ("CO2", Keep::LeastCommon)] {
+        let Some(rating) = report.rating(keep) else { continue };
+        show(name, &rating.value);
+        if opts.candidates {
+            let counts: Vec<String> = rating.candidates.iter().map(|n| n.to_string()).collect();
+            println!("         candidates: {}", counts.join(" "));
         }
+    }
+}
+
+pub fn day03(_test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input03.txt";
+    let input_str = std::fs::read_to_string(INPUT).unwrap();
 
-        value <<= 1;
-        if next_ones * 2 < next_total { value += 1; }
+    let (part1, part2) = solve(&input_str);
+    if print {
+        println!("Part 1: {}", part1);
     }
-    let co2 = value;
+    assert_eq!(part1, 3959450);
 
     if print {
-        println!("Part 2: {}", oxygen * co2);
+        println!("Part 2: {}", part2);
+    }
+    assert_eq!(part2, 7440311);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010\n";
+
+    fn rating(report: &Diagnostic, keep: Keep) -> String {
+        report.rating(keep).unwrap().value.to_string()
+    }
+
+    #[test]
+    fn example() {
+        let report = Diagnostic::parse(EXAMPLE).unwrap();
+        assert_eq!((report.gamma().to_string(), report.epsilon().to_string()), ("10110".to_string(), "01001".to_string()));
+        assert_eq!(rating(&report, Keep::MostCommon), "10111");
+        assert_eq!(rating(&report, Keep::LeastCommon), "01010");
+        assert_eq!(report.rating(Keep::MostCommon).unwrap().candidates, vec![12, 7, 4, 3, 2]);
+    }
+
+    #[test]
+    fn ties() {
+        // An even split keeps the ones for oxygen and the zeros for CO2, at every bit.
+        let report = Diagnostic::parse("00\n01\n10\n11\n").unwrap();
+        assert_eq!(rating(&report, Keep::MostCommon), "11");
+        assert_eq!(rating(&report, Keep::LeastCommon), "00");
+        // Once one reading is left, it's followed whatever the counts say.
+        let report = Diagnostic::parse("011\n100\n101\n").unwrap();
+        assert_eq!(rating(&report, Keep::LeastCommon), "011");
+        assert_eq!(report.rating(Keep::LeastCommon).unwrap().candidates, vec![3, 1, 1]);
+        assert!(Diagnostic::new(3).rating(Keep::MostCommon).is_none());
+    }
+
+    #[test]
+    fn wide_readings() {
+        let wide = |fill: char, last: char| format!("{}{}\n", fill.to_string().repeat(79), last);
+        let input = [wide('1', '0'), wide('1', '1'), wide('0', '1')].concat();
+        let report = Diagnostic::parse(&input).unwrap();
+        assert_eq!(report.width(), 80);
+        let oxygen = report.rating(Keep::MostCommon).unwrap().value;
+        assert_eq!(oxygen.to_string(), wide('1', '1').trim_end());
+        assert_eq!(oxygen.to_u64(), None);
+        assert_eq!(rating(&report, Keep::LeastCommon), wide('0', '1').trim_end());
+        assert_eq!(Diagnostic::read(input.as_bytes()).unwrap().gamma(), report.gamma());
+    }
+
+    #[test]
+    fn leading_blank_lines() {
+        let report = Diagnostic::parse(&format!("\n  \n{}", EXAMPLE)).unwrap();
+        assert_eq!((report.len(), report.width()), (12, 5));
+        assert_eq!(Diagnostic::parse("\n101\n11\n").err(), Some("Line 3: Expected 3 bits, found \"11\"".to_string()));
     }
-    assert_eq!(oxygen * co2, 7440311);
 }
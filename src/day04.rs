@@ -1,66 +1,351 @@
-//use std::collections::HashMap;
+use std::fs;
+
+use clap::Args;
 use rustc_hash::FxHashMap;
 
 use crate::utils::*;
 
+// Which patterns of marked numbers win. Any one of the enabled ones is enough.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rules {
+    pub rows: bool,
+    pub cols: bool,
+    // Both main diagonals, on square boards only.
+    pub diagonals: bool,
+    pub corners: bool,
+    pub blackout: bool,
+}
 
-pub fn day04(_test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input04.txt";
-    let input_str = std::fs::read_to_string(INPUT).unwrap();
-
-    let mut parts = input_str.split("\n\n");
-
-    let called: Vec<i32> = parts.next().unwrap().split(",")
-        .map(|s| s.parse::<i32>().unwrap()).collect();
-
-    // Parses the boards
-    let mut board_lookup = FxHashMap::<i32, Vec<(usize, usize, usize)>>::default();
-
-    let mut board_sums = Vec::<usize>::with_capacity(20);
-    for (b, board_str) in parts.enumerate() {
-        let mut board_sum = 0usize;
-        for (r, row) in board_str.split("\n").enumerate() {
-            for (c, num_str) in row.split_ascii_whitespace().enumerate() {
-                let num = num_str.parse::<i32>().unwrap();
-                board_sum += num as usize;
-                board_lookup.entry(num)
-                    .or_insert(Vec::with_capacity(8))
-                    .push((b, r, c));
+impl Rules {
+    pub const CLASSIC: Rules = Rules{rows: true, cols: true, diagonals: false, corners: false, blackout: false};
+}
+
+#[derive(Clone)]
+pub struct Board {
+    pub numbers: Grid<u32>,
+}
+
+impl Board {
+    fn corners(&self) -> Vec<(usize, usize)> {
+        let (last_r, last_c) = (self.numbers.rows - 1, self.numbers.cols - 1);
+        let mut corners = vec![(0, 0), (0, last_c), (last_r, 0), (last_r, last_c)];
+        // Boards one row or column wide share corners.
+        corners.sort();
+        corners.dedup();
+        corners
+    }
+
+    // The board with every called number highlighted.
+    pub fn fmt_marked(&self, called: &[u32]) -> String {
+        let marked = self.numbers.data.iter().enumerate()
+            .filter(|(_, num)| called.contains(num))
+            .map(|(idx, _)| (idx / self.numbers.cols, idx % self.numbers.cols));
+        self.numbers.fmt_table_highlighted(marked, "1;32")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win {
+    pub board: usize,
+    // Index into the called numbers.
+    pub turn: usize,
+    pub number: u32,
+    // The sum of the unmarked numbers times the winning number.
+    pub score: usize,
+}
+
+// How far along one board is.
+struct Card {
+    marked: Grid<bool>,
+    row_marks: Vec<usize>,
+    col_marks: Vec<usize>,
+    diag_marks: [usize; 2],
+    corner_marks: usize,
+    total_marks: usize,
+    unmarked_sum: usize,
+    won: bool,
+}
+
+impl Card {
+    fn new(board: &Board) -> Self {
+        let nums = &board.numbers;
+        Card{
+            marked: Grid::filled(nums.rows, nums.cols, false),
+            row_marks: vec![0; nums.rows],
+            col_marks: vec![0; nums.cols],
+            diag_marks: [0; 2],
+            corner_marks: 0,
+            total_marks: 0,
+            unmarked_sum: nums.data.iter().map(|num| *num as usize).sum(),
+            won: false,
+        }
+    }
+
+    // Marks a cell, and says whether that completes a winning pattern.
+    fn mark(&mut self, board: &Board, (r, c): (usize, usize), rules: &Rules, corners: &[(usize, usize)]) -> bool {
+        if self.marked[(r, c)] {
+            return false;
+        }
+        let (rows, cols) = (board.numbers.rows, board.numbers.cols);
+        self.marked[(r, c)] = true;
+        self.unmarked_sum -= board.numbers[(r, c)] as usize;
+        self.row_marks[r] += 1;
+        self.col_marks[c] += 1;
+        self.total_marks += 1;
+        let mut on_diagonal = false;
+        if rules.diagonals && rows == cols {
+            if r == c {
+                self.diag_marks[0] += 1;
+                on_diagonal |= self.diag_marks[0] == rows;
+            }
+            if r + c == cols - 1 {
+                self.diag_marks[1] += 1;
+                on_diagonal |= self.diag_marks[1] == rows;
+            }
+        }
+        let mut on_corners = false;
+        if rules.corners && corners.contains(&(r, c)) {
+            self.corner_marks += 1;
+            on_corners = self.corner_marks == corners.len();
+        }
+
+        (rules.rows && self.row_marks[r] == cols)
+            || (rules.cols && self.col_marks[c] == rows)
+            || on_diagonal
+            || on_corners
+            || (rules.blackout && self.total_marks == rows * cols)
+    }
+}
+
+pub struct Bingo {
+    pub called: Vec<u32>,
+    pub boards: Vec<Board>,
+}
+
+impl Bingo {
+    // The called numbers on the first line, then boards of any size separated by blank lines.
+    pub fn parse(input_str: &str) -> Result<Bingo, String> {
+        let mut lines = input_str.lines().map(str::trim);
+        let called = lines.by_ref().find(|line| !line.is_empty()).unwrap_or("").split(',')
+            .map(|s| s.trim().parse().map_err(|_| format!("Bad called number: {:?}", s)))
+            .collect::<Result<Vec<u32>, String>>()?;
+
+        let mut boards = Vec::with_capacity(100);
+        let mut data = Vec::with_capacity(25);
+        let (mut rows, mut cols) = (0, 0);
+        // The blank line on the end closes off the last board.
+        for line in lines.chain([""]) {
+            if line.is_empty() {
+                if rows > 0 {
+                    boards.push(Board{numbers: Grid{rows, cols, data: std::mem::take(&mut data)}});
+                    rows = 0;
+                }
+                continue;
+            }
+            let start = data.len();
+            for num_str in line.split_ascii_whitespace() {
+                data.push(num_str.parse().map_err(|_| format!("Bad number on board {}: {:?}", boards.len(), num_str))?);
+            }
+            let len = data.len() - start;
+            if rows == 0 {
+                cols = len;
+            } else if len != cols {
+                return Err(format!("Board {} has rows of different lengths ({} and {})", boards.len(), cols, len));
             }
+            rows += 1;
         }
-        board_sums.push(board_sum);
-    }
-
-    const K: usize = 5;
-
-    // board_rows[board, row] = how many numbers are marked in that row
-    let mut board_rows = Grid{rows: board_sums.len(), cols: K, data: vec![0; board_sums.len() * K]};
-    let mut board_cols = Grid{rows: board_sums.len(), cols: K, data: vec![0; board_sums.len() * K]};
-
-    let mut board_won = vec![false; board_sums.len()];
-    let mut num_won = 0;
-    let mut first_score = 0;
-    let mut last_score = 0;
-    for called_num in called {
-        for (b, r, c) in board_lookup.get(&called_num).unwrap() {
-            board_rows[(*b, *r)] += 1;
-            board_cols[(*b, *c)] += 1;
-            board_sums[*b] -= called_num as usize;
-
-            if !board_won[*b] && (board_rows[(*b, *r)] == K || board_cols[(*b, *c)] == K) {
-                board_won[*b] = true;
-                num_won += 1;
-                let score = board_sums[*b] * called_num as usize;
-                if num_won == 1 {
-                    first_score = score;
+        Ok(Bingo{called, boards})
+    }
+
+    // Every board that wins, in the order they win. Boards winning on the same number go in
+    // board order.
+    pub fn play(&self, rules: &Rules) -> Vec<Win> {
+        let mut places = FxHashMap::<u32, Vec<(usize, (usize, usize))>>::default();
+        for (b, board) in self.boards.iter().enumerate() {
+            for (idx, num) in board.numbers.data.iter().enumerate() {
+                places.entry(*num).or_insert_with(|| Vec::with_capacity(8))
+                    .push((b, (idx / board.numbers.cols, idx % board.numbers.cols)));
+            }
+        }
+        let corners: Vec<_> = self.boards.iter().map(|board| board.corners()).collect();
+
+        let mut cards: Vec<Card> = self.boards.iter().map(Card::new).collect();
+        let mut wins = Vec::with_capacity(self.boards.len());
+        for (turn, &number) in self.called.iter().enumerate() {
+            let Some(places) = places.get(&number) else { continue };
+            for &(b, rc) in places {
+                let card = &mut cards[b];
+                if card.mark(&self.boards[b], rc, rules, &corners[b]) && !card.won {
+                    card.won = true;
+                    wins.push(Win{board: b, turn, number, score: card.unmarked_sum * number as usize});
                 }
-                last_score = score;
             }
+            if wins.len() == self.boards.len() {
+                break;
+            }
+        }
+        wins
+    }
+}
+
+// The scores of the first and last boards to win, or None if none of them do.
+pub fn solve(input_str: &str) -> Option<(usize, usize)> {
+    let bingo = Bingo::parse(input_str).unwrap();
+    let wins = bingo.play(&Rules::CLASSIC);
+    Some((wins.first()?.score, wins.last()?.score))
+}
+
+#[derive(Args)]
+pub struct BingoOpts {
+    /// Puzzle file: called numbers, then boards separated by blank lines
+    file: String,
+    /// Completing either main diagonal wins too
+    #[clap(long)]
+    diagonals: bool,
+    /// Marking all four corners wins too
+    #[clap(long)]
+    corners: bool,
+    /// Marking the whole board wins too
+    #[clap(long)]
+    blackout: bool,
+    /// Rows and columns don't count
+    #[clap(long)]
+    no_lines: bool,
+    /// Also prints each winning board as it stood when it won, with the called numbers marked
+    #[clap(long)]
+    show: bool,
+}
+
+// Ranks the boards by when they win under the chosen rules.
+pub fn bingo(opts: &BingoOpts) {
+    let bingo = match fs::read_to_string(&opts.file).map_err(|err| err.to_string()).and_then(|s| Bingo::parse(&s)) {
+        Ok(bingo) => bingo,
+        Err(err) => {
+            eprintln!("{}: {}", opts.file, err);
+            std::process::exit(1);
+        }
+    };
+    let rules = Rules{
+        rows: !opts.no_lines,
+        cols: !opts.no_lines,
+        diagonals: opts.diagonals,
+        corners: opts.corners,
+        blackout: opts.blackout,
+    };
+
+    let wins = bingo.play(&rules);
+    let mut table = Grid::filled(wins.len() + 1, 5, String::new());
+    for (c, heading) in ["Place", "Board", "Turn", "Number", "Score"].iter().enumerate() {
+        table[(0, c)] = heading.to_string();
+    }
+    for (place, win) in wins.iter().enumerate() {
+        let row = [place + 1, win.board, win.turn + 1, win.number as usize, win.score];
+        for (c, value) in row.iter().enumerate() {
+            table[(place + 1, c)] = value.to_string();
         }
     }
+    print!("{}", tabulate(&table));
+    if opts.show {
+        for (place, win) in wins.iter().enumerate() {
+            println!("\n#{}: board {} on turn {}", place + 1, win.board, win.turn + 1);
+            print!("{}", bingo.boards[win.board].fmt_marked(&bingo.called[..=win.turn]));
+        }
+    }
+    if wins.len() < bingo.boards.len() {
+        println!("{} of {} boards never win", bingo.boards.len() - wins.len(), bingo.boards.len());
+    }
+}
+
+pub fn day04(_test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input04.txt";
+    let input_str = fs::read_to_string(INPUT).unwrap();
+
+    let (first_score, last_score) = solve(&input_str).expect("No board wins");
     if print {
         println!("Answers: {} {}", first_score, last_score);
     }
     assert_eq!(first_score, 27027);
     assert_eq!(last_score, 36975);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Board 0 gets a diagonal, then its corners, then a row, and is blacked out last. Board 1
+    // only ever gets its corners.
+    const GAME: &str = "\
+1,5,9,3,7,20,40,60,50,2,4,6,8
+
+1 2 3
+4 5 6
+7 8 9
+
+20 99 40
+98 97 96
+50 95 60
+";
+
+    fn winners(bingo: &Bingo, rules: Rules) -> Vec<(usize, u32)> {
+        bingo.play(&rules).iter().map(|win| (win.board, win.number)).collect()
+    }
+
+    #[test]
+    fn rule_variants() {
+        let bingo = Bingo::parse(GAME).unwrap();
+        assert_eq!(winners(&bingo, Rules::CLASSIC), vec![(0, 2)]);
+        let none = Rules::default();
+        assert_eq!(winners(&bingo, Rules{diagonals: true, ..none}), vec![(0, 9)]);
+        assert_eq!(winners(&bingo, Rules{corners: true, ..none}), vec![(0, 7), (1, 50)]);
+        assert_eq!(winners(&bingo, Rules{blackout: true, ..none}), vec![(0, 8)]);
+        assert!(winners(&bingo, none).is_empty());
+
+        // A diagonal scores what's left off it.
+        let win = bingo.play(&Rules{diagonals: true, ..none})[0];
+        assert_eq!((win.turn, win.score), (2, (2 + 3 + 4 + 6 + 7 + 8) * 9));
+    }
+
+    #[test]
+    fn diagonals_need_square_boards() {
+        let bingo = Bingo::parse("1,2,3\n\n1 0 0\n0 2 0\n").unwrap();
+        assert!(bingo.play(&Rules{diagonals: true, ..Rules::default()}).is_empty());
+        // A single row's corners are just its two ends.
+        let bingo = Bingo::parse("5,6\n\n5 6\n").unwrap();
+        assert_eq!(winners(&bingo, Rules{corners: true, ..Rules::default()}), vec![(0, 6)]);
+    }
+
+    #[test]
+    fn solve_without_a_winner() {
+        assert_eq!(solve("1,2\n\n1 3\n4 2\n"), None);
+        assert_eq!(solve("1\n"), None);
+        assert_eq!(solve("1,4,5,2\n\n1 3\n4 2\n\n5 1\n2 6\n"), Some(((3 + 2) * 4, (2 + 6) * 5)));
+    }
+
+    #[test]
+    fn fmt_marked() {
+        let board = &Bingo::parse(GAME).unwrap().boards[1];
+        assert_eq!(board.fmt_marked(&[]), "20 99 40\n98 97 96\n50 95 60\n");
+        let marked = board.fmt_marked(&[40, 1, 95]);
+        let lines: Vec<&str> = marked.lines().collect();
+        assert_eq!(lines[0], format!("20 99 {}", paint("40", "1;32")));
+        assert_eq!(lines[1], "98 97 96");
+        assert_eq!(lines[2], format!("50 {} 60", paint("95", "1;32")));
+    }
+
+    #[test]
+    fn parses_crlf() {
+        let bingo = Bingo::parse(&GAME.replace('\n', "\r\n")).unwrap();
+        assert_eq!(bingo.called.len(), 13);
+        assert_eq!(bingo.boards.len(), 2);
+        assert_eq!((bingo.boards[1].numbers.rows, bingo.boards[1].numbers.cols), (3, 3));
+        assert_eq!(bingo.boards[1].numbers.data, Bingo::parse(GAME).unwrap().boards[1].numbers.data);
+    }
+
+    #[test]
+    fn rejects_ragged_boards() {
+        // Six numbers in two rows, but not two rows of three.
+        assert_eq!(Bingo::parse("1\n\n1 2\n3 4 5 6\n").err(),
+            Some("Board 0 has rows of different lengths (2 and 4)".to_string()));
+        assert!(Bingo::parse("1\n\n1 2 3\n4 5 6\n\n7 8\n9\n").is_err());
+    }
+}
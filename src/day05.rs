@@ -1,6 +1,7 @@
-use regex::Regex;
-//use std::collections::HashMap;
-use rustc_hash::FxHashMap;
+use std::cmp::max;
+use std::io;
+
+use crate::utils::*;
 
 #[derive(Debug)]
 struct Line {
@@ -41,47 +42,110 @@ fn iter_along(a: i32, b: i32) -> AlongIterator {
     AlongIterator{next: a, end: b, step: (b - a).signum()}
 }
 
-pub fn day05(_test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input05.txt";
-    let input_str = std::fs::read_to_string(INPUT).unwrap();
-
-    let re_parse_line = Regex::new(r"(\d+),(\d+) -> (\d+),(\d+)\n").unwrap();
+// "x0,y0 -> x1,y1"
+fn parse_line(scan: &mut ByteScanner) -> Option<Line> {
+    let x0 = scan.int()?;
+    scan.tag(",")?;
+    let y0 = scan.int()?;
+    scan.tag(" -> ")?;
+    let x1 = scan.int()?;
+    scan.tag(",")?;
+    let y1 = scan.int()?;
+    scan.skip_whitespace();
+    Some(Line{x0, y0, x1, y1})
+}
 
+fn parse_lines(input_str: &str) -> Vec<Line> {
+    let mut scan = ByteScanner::new(input_str);
     let mut lines = Vec::<Line>::with_capacity(1000);
-    for cap in re_parse_line.captures_iter(&input_str) {
-        lines.push(Line{
-            x0: cap[1].parse().unwrap(),
-            y0: cap[2].parse().unwrap(),
-            x1: cap[3].parse().unwrap(),
-            y1: cap[4].parse().unwrap(),
-        });
+    scan.skip_whitespace();
+    while !scan.at_end() {
+        let at = scan.clone();
+        lines.push(parse_line(&mut scan).unwrap_or_else(|| panic!("Bad line: {:?}", at.clone().line().unwrap_or(""))));
     }
+    lines
+}
+
+fn heat(count: u8) -> Rgb {
+    match count {
+        0 => [0, 0, 0],
+        1 => [40, 60, 140],
+        2 => [230, 120, 30],
+        _ => [255, 240, 120],
+    }
+}
+
+// How many lines cover each cell of their bounding box, first counting only the horizontal
+// and vertical lines, then with the diagonals too. Cell (0, 0) is the box's top left corner,
+// which needn't be the origin.
+fn overlap_counts(lines: &[Line]) -> [Grid<u8>; 2] {
+    let xs = lines.iter().fold(Interval::EMPTY, |xs, l| xs.hull(Interval::spanning(l.x0, l.x1)));
+    let ys = lines.iter().fold(Interval::EMPTY, |ys, l| ys.hull(Interval::spanning(l.y0, l.y1)));
+
+    let add = |counts: &mut Grid<u8>, diagonals: bool| {
+        for line in lines {
+            let is_diagonal = line.x0 != line.x1 && line.y0 != line.y1;
+            if is_diagonal != diagonals {
+                continue;
+            }
+            // Along a fixed coordinate, iter_along just repeats it.
+            let steps = max((line.x1 - line.x0).abs(), (line.y1 - line.y0).abs()) as usize + 1;
+            for (x, y) in iter_along(line.x0, line.x1).zip(iter_along(line.y0, line.y1)).take(steps) {
+                let count = &mut counts[((y - ys.lo) as usize, (x - xs.lo) as usize)];
+                *count = count.saturating_add(1);
+            }
+        }
+    };
+    let mut straight = Grid::filled(ys.len(), xs.len(), 0u8);
+    add(&mut straight, false);
+    let mut all = straight.clone();
+    add(&mut all, true);
+    [straight, all]
+}
+
+// Two heatmaps of vent overlaps: horizontal and vertical lines only, then with the diagonals.
+pub fn export(input_str: &str, frames: &mut FrameWriter) -> io::Result<()> {
+    for counts in overlap_counts(&parse_lines(input_str)) {
+        frames.ppm(&Grid{rows: counts.rows, cols: counts.cols, data: counts.data.iter().map(|c| heat(*c)).collect()})?;
+    }
+    Ok(())
+}
 
-    let mut horvert_locs = FxHashMap::<(i32, i32), i32>::default();
-    let mut locs = FxHashMap::<(i32, i32), i32>::default();
-    locs.reserve(100000);
-    horvert_locs.reserve(100000);
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let lines = parse_lines(input_str);
+
+    let mut horvert_locs = SparseGrid::with_capacity(0, 100000);
+    let mut locs = SparseGrid::with_capacity(0, 100000);
     for line in lines {
         if line.x0 == line.x1 {
-            for y in iter_along(line.y0, line.y1) {
-                *horvert_locs.entry((line.x0, y)).or_insert(0) += 1;
-                *locs.entry((line.x0, y)).or_insert(0) += 1;
+            for y in Interval::spanning(line.y0, line.y1) {
+                *horvert_locs.get_mut(Pt::at(line.x0, y)) += 1;
+                *locs.get_mut(Pt::at(line.x0, y)) += 1;
             }
         } else if line.y0 == line.y1 {
-            for x in iter_along(line.x0, line.x1) {
-                *horvert_locs.entry((x, line.y0)).or_insert(0) += 1;
-                *locs.entry((x, line.y0)).or_insert(0) += 1;
+            for x in Interval::spanning(line.x0, line.x1) {
+                *horvert_locs.get_mut(Pt::at(x, line.y0)) += 1;
+                *locs.get_mut(Pt::at(x, line.y0)) += 1;
             }
         } else {
             for (x, y) in iter_along(line.x0, line.x1).zip(iter_along(line.y0, line.y1)) {
-                *locs.entry((x, y)).or_insert(0) += 1;
+                *locs.get_mut(Pt::at(x, y)) += 1;
             }
         }
     }
 
     //println!("Hash size: {}", locs.len());
-    let part1 = horvert_locs.into_values().filter(|cnt| *cnt > 1).count();
-    let part2 = locs.into_values().filter(|cnt| *cnt > 1).count();
+    let part1 = horvert_locs.values().filter(|cnt| **cnt > 1).count();
+    let part2 = locs.values().filter(|cnt| **cnt > 1).count();
+
+    (part1, part2)
+}
+
+pub fn day05(_test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input05.txt";
+    let input_str = std::fs::read_to_string(INPUT).unwrap();
+
+    let (part1, part2) = solve(&input_str);
     if print {
         println!("Part 1: {}", part1);
     }
@@ -91,3 +155,41 @@ pub fn day05(_test_mode: bool, print: bool) {
     }
     assert_eq!(part2, 22088);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_every_line() {
+        let lines = parse_lines("0,9 -> 5,9\n8,0 -> 0,8\r\n9,4 -> 3,4\n\n");
+        assert_eq!(lines.len(), 3);
+        assert_eq!((lines[2].x0, lines[2].y0, lines[2].x1, lines[2].y1), (9, 4, 3, 4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Bad line: \"9,4 -> 3\"")]
+    fn rejects_a_bad_line() {
+        parse_lines("0,9 -> 5,9\n9,4 -> 3\n2,2 -> 2,1\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "Bad line")]
+    fn rejects_overflow() {
+        parse_lines("0,9 -> 5,9\n9,4 -> 3,4000000000\n");
+    }
+
+    #[test]
+    fn overlaps_with_negative_coordinates() {
+        let lines = parse_lines("-2,-1 -> 1,-1\n0,-3 -> 0,0\n-2,-3 -> 1,0\n");
+        let [straight, all] = overlap_counts(&lines);
+        // x from -2 to 1, y from -3 to 0.
+        assert_eq!((straight.rows, straight.cols), (4, 4));
+        assert_eq!(straight[(2, 2)], 2);
+        assert_eq!(straight.data.iter().filter(|c| **c > 0).count(), 7);
+        // The diagonal runs corner to corner, through where the other two cross.
+        assert_eq!(all[(2, 2)], 3);
+        assert_eq!((all[(0, 0)], all[(1, 1)], all[(3, 3)]), (1, 1, 1));
+        assert_eq!(all.data.iter().map(|c| *c as usize).sum::<usize>(), 12);
+    }
+}
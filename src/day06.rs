@@ -1,20 +1,16 @@
-pub fn day06(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input06.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        "3,4,3,1,2"
-    } else {
-        file_str.trim_end()
-    };
+use crate::utils::*;
 
-    let mut current = vec![0usize; 9];
-    for num_str in input_str.split(",") {
-        current[num_str.parse::<usize>().unwrap()] += 1;
-    }
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let input_str = input_str.trim_end();
+
+    // The number of fish with each timer value.
+    let mut current: [usize; 9] = input_str.split(",")
+        .map(|num_str| num_str.parse::<usize>().unwrap())
+        .counts_array();
     
     let mut part1 = 0;
     for d in 1..=256 {
-        let mut next = vec![0usize; 9];
+        let mut next = [0usize; 9];
         for (n, cnt) in current.iter().enumerate() {
             if n == 0 {
                 next[6] += cnt;
@@ -29,9 +25,23 @@ pub fn day06(test_mode: bool, print: bool) {
             part1 = current.iter().sum::<usize>();
         }
     }
+    let part2 = current.iter().sum::<usize>();
+
+    (part1, part2)
+}
+
+pub fn day06(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input06.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        "3,4,3,1,2"
+    } else {
+        &file_str
+    };
+
+    let (part1, part2) = solve(input_str);
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 5934 } else { 351188 });
-    let part2 = current.iter().sum::<usize>();
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 26984457539 } else { 1595779846729 });
 }
@@ -1,9 +1,10 @@
 use std::cmp::min;
 
+use crate::utils::*;
+
 
 fn optimize_cost(input: &[i32], cost_at: impl Fn(&[i32], i32) -> i32) -> i32 {
-    let mut lo = *input.iter().min().unwrap();
-    let mut hi = *input.iter().max().unwrap();
+    let (mut lo, mut hi) = input.iter().copied().minmax().unwrap();
 
     loop {
         let mid = (lo + hi) / 2;
@@ -23,21 +24,14 @@ fn optimize_cost(input: &[i32], cost_at: impl Fn(&[i32], i32) -> i32) -> i32 {
     }
 }
 
-pub fn day07(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input07.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        "16,1,2,0,4,2,7,1,2,14"
-    } else {
-        file_str.trim_end()
-    };
+pub fn solve(input_str: &str) -> (i32, i32) {
+    let input_str = input_str.trim_end();
 
     let input = input_str
         .split(",")
         .map(|s| s.parse::<i32>().unwrap())
         .collect::<Vec<_>>();
 
-
     let cost_at = |input: &[i32], pt: i32| {
         let mut cost = 0;
         for x in input {
@@ -47,9 +41,6 @@ pub fn day07(test_mode: bool, print: bool) {
     };
 
     let part1 = optimize_cost(&input, cost_at);
-    if print { println!("Part 1: {}", part1); }
-    assert_eq!(part1, if test_mode { 37 } else { 356922 });
-
 
     let cost_at_quadratic = |input: &[i32], pt: i32| {
         let mut cost = 0;
@@ -61,6 +52,22 @@ pub fn day07(test_mode: bool, print: bool) {
     };
 
     let part2 = optimize_cost(&input, cost_at_quadratic);
+
+    (part1, part2)
+}
+
+pub fn day07(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input07.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        "16,1,2,0,4,2,7,1,2,14"
+    } else {
+        &file_str
+    };
+
+    let (part1, part2) = solve(input_str);
+    if print { println!("Part 1: {}", part1); }
+    assert_eq!(part1, if test_mode { 37 } else { 356922 });
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 168 } else { 100347031 });
 }
@@ -1,50 +1,97 @@
+use std::cmp::Reverse;
+use std::io;
+
 use crate::utils::*;
 
 
-fn lookup_canonical_and_collapse(collapsing: &mut [i32], idx: i32) -> i32 {
-    if idx < 0 {
-        idx
-    } else if collapsing[idx as usize] == idx {
-        idx
-    } else {
-        let base = lookup_canonical_and_collapse(collapsing, collapsing[idx as usize]);
-        collapsing[idx as usize] = base;
-        base
-    }
+fn parse_grid(input_str: &str) -> Grid<u8> {
+    Grid::parse(input_str, |ch| ch - b'0').unwrap()
 }
 
-pub fn day09(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input09.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        "2199943210
-3987894921
-9856789892
-8767896789
-9899965678"
-    } else {
-        &file_str
-    };
-    
-    let mut grid_data = Vec::<u8>::with_capacity(50 * 50);
-    let mut grid_rows = 0usize;
-    let mut grid_cols = 0usize;
-    for line in input_str.lines() {
-        let line = line.trim_start();
-        grid_cols = line.len();
-        grid_rows += 1;
-
-        for ch in line.bytes() {
-            grid_data.push(ch - b'0');
+// Every cell starts out labeled like its left or upper neighbor, or with a new label,
+// and labels that turn out to meet are joined in the DisjointSet. Only the labels go in the
+// set rather than every cell, which keeps it small.
+struct Basins {
+    labels: Grid<u32>,  // u32::MAX for the 9s
+    joined: DisjointSet,
+    cells: Vec<usize>,  // Per label
+}
+
+impl Basins {
+    fn find(grid: &Grid<u8>) -> Basins {
+        const NONE: u32 = u32::MAX;
+        let mut labels = Grid::filled(grid.rows, grid.cols, NONE);
+        let mut joined = DisjointSet::new(0);
+        let mut cells = Vec::<usize>::with_capacity(4 * grid.rows);
+        for r in 0..grid.rows {
+            for c in 0..grid.cols {
+                if grid[(r, c)] == 9 { continue; }
+                let left = if c > 0 { labels[(r, c - 1)] } else { NONE };
+                let above = if r > 0 { labels[(r - 1, c)] } else { NONE };
+                let label =
+                    if left != NONE {
+                        if above != NONE && above != left {
+                            joined.union(left as usize, above as usize);
+                        }
+                        left
+                    } else if above != NONE {
+                        above
+                    } else {
+                        cells.push(0);
+                        joined.add() as u32
+                    };
+                labels[(r, c)] = label;
+                cells[label as usize] += 1;
+            }
         }
+        Basins{labels, joined, cells}
     }
 
-    let grid = Grid{rows: grid_rows, cols: grid_cols, data: grid_data};
+    // Which basin the cell is in, or None for the 9s.
+    fn basin_of(&mut self, r: usize, c: usize) -> Option<usize> {
+        let label = self.labels[(r, c)];
+        if label == u32::MAX { None } else { Some(self.joined.find(label as usize)) }
+    }
+
+    // The number of cells in each basin.
+    fn sizes(&mut self) -> Vec<usize> {
+        let mut sizes = vec![0; self.cells.len()];
+        for label in 0..self.cells.len() {
+            sizes[self.joined.find(label)] += self.cells[label];
+        }
+        sizes.retain(|size| *size > 0);
+        sizes
+    }
+}
+
+// The heightmap in grayscale, then the basins in (arbitrary) colors.
+pub fn export(input_str: &str, frames: &mut FrameWriter) -> io::Result<()> {
+    let grid = parse_grid(input_str);
+    frames.pgm(&Grid{rows: grid.rows, cols: grid.cols, data: grid.data.iter().map(|h| h * 28).collect()})?;
+
+    let mut basins = Basins::find(&grid);
+    let mut basin_color = |(r, c)| -> Rgb {
+        let basin = match basins.basin_of(r, c) {
+            Some(basin) => basin,
+            None => return [0, 0, 0],
+        };
+        // Scatters neighboring basins across the color wheel.
+        let hash = (basin as u32).wrapping_mul(2654435761);
+        [64 + (hash >> 24) as u8 % 192, 64 + (hash >> 16) as u8 % 192, 64 + (hash >> 8) as u8 % 192]
+    };
+    let colors = (0..grid.rows).flat_map(|r| (0..grid.cols).map(move |c| (r, c))).map(&mut basin_color).collect();
+    frames.ppm(&Grid{rows: grid.rows, cols: grid.cols, data: colors})?;
+    Ok(())
+}
+
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let grid = parse_grid(input_str);
 
     let mut part1: usize = 0;
     for r in 0..grid.rows {
         for c in 0..grid.cols {
             let value = grid[(r, c)];
+            // Spelled out rather than with neighbors4, since this is the hot loop.
             let is_min =
                 (r == 0 || grid[(r - 1, c)] > value) &&
                 (c == 0 || grid[(r, c - 1)] > value) &&
@@ -55,60 +102,31 @@ pub fn day09(test_mode: bool, print: bool) {
             }
         }
     }
-    if print { println!("Part 1: {}", part1); }
-    assert_eq!(part1, if test_mode { 15 } else { 548 });
 
     // Really 9's are just the boundaries, and every other value is the same.
 
-    let mut basin_sizes = Vec::<usize>::with_capacity(2 * grid.rows);
-    let mut basin_collapses_to = Vec::<i32>::with_capacity(2 * grid.rows);
-
-    let mut last_basins: Vec<i32> = vec![-1; grid.cols];
-    let mut next_basin_id = 0i32;
+    let sizes = Basins::find(&grid).sizes().into_iter().sorted_by_key(|size| Reverse(*size));
+    let part2 = sizes[..3].iter().product();
 
-    for r in 0..grid.rows {
-        let mut current_basins = vec![-1; grid.cols];
-        for c in 0..grid.cols {
-            if grid[(r, c)] == 9 { continue; }
-            let basin_above = lookup_canonical_and_collapse(&mut basin_collapses_to, last_basins[c]);
-
-            if c > 0 && current_basins[c - 1] >= 0 {
-                // Continues the horizontal basin.
-                let basin = current_basins[c - 1];
-                basin_sizes[basin as usize] += 1;
-                current_basins[c] = basin;
-
-                // Checks the vertical basin.
-                if basin_above >= 0 && basin_above != basin {
-                    // Collapse
-                    basin_collapses_to[basin_above as usize] = basin;
-                    basin_sizes[basin as usize] += basin_sizes[basin_above as usize];
-                }
-            } else if basin_above >= 0 {
-                // Continues the vertical basin.
-                basin_sizes[basin_above as usize] += 1;
-                current_basins[c] = basin_above;
-            } else {
-                assert_eq!(basin_sizes.len(), next_basin_id as usize); assert_eq!(basin_collapses_to.len(), next_basin_id as usize);
-                // New basin!
-                basin_sizes.push(1);
-                basin_collapses_to.push(next_basin_id);
-                current_basins[c] = next_basin_id;
-                next_basin_id += 1;
-            }
-        }
+    (part1, part2)
+}
 
-        last_basins = current_basins;
-    }
+pub fn day09(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input09.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        "2199943210
+3987894921
+9856789892
+8767896789
+9899965678"
+    } else {
+        &file_str
+    };
 
-    let mut top3 = vec![0; 4];
-    for basin in 0..basin_sizes.len() {
-        if basin_collapses_to[basin] == basin as i32 {
-            top3[0] = basin_sizes[basin];
-            top3.sort();
-        }
-    }
-    let part2 = top3[1] * top3[2] * top3[3];
+    let (part1, part2) = solve(input_str);
+    if print { println!("Part 1: {}", part1); }
+    assert_eq!(part1, if test_mode { 15 } else { 548 });
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 1134 } else { 786048 });
 }
@@ -32,24 +32,7 @@ fn incomplete_score_of(ch: u8) -> usize {
     }
 }
 
-pub fn day10(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input10.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        "[({(<(())[]>[[{[]{<()<>>
-[(()[<>])]({[<{<<[]>>(
-{([(<{}[<>[]}>{[]{[(<()>
-(((({<>}<{<{<>}{[]{[]{}
-[[<[([]))<([[{}[[()]]]
-[{[{({}]{}}([{[{{{}}([]
-{<[[]]>}<{[{[{[]{()[[[]
-[<(<(<(<{}))><([]([]()
-<{([([[(<>()){}]>(<<{{
-<{([{{}}[<[[[<>{}]]]>[]]"
-    } else {
-        &file_str
-    };
-
+pub fn solve(input_str: &str) -> (i32, usize) {
     let mut part1 = 0;
     let mut incomplete_scores = Vec::<usize>::with_capacity(100);
     'byline: for line in input_str.lines() {
@@ -75,11 +58,33 @@ pub fn day10(test_mode: bool, print: bool) {
         incomplete_scores.push(incomplete_score);
     }
 
-    if print { println!("Part 1: {}", part1); }
-    assert_eq!(part1, if test_mode { 26397 } else { 265527 });
-
     incomplete_scores.sort();
     let part2 = incomplete_scores[incomplete_scores.len() / 2];
+
+    (part1, part2)
+}
+
+pub fn day10(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input10.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        "[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]"
+    } else {
+        &file_str
+    };
+
+    let (part1, part2) = solve(input_str);
+    if print { println!("Part 1: {}", part1); }
+    assert_eq!(part1, if test_mode { 26397 } else { 265527 });
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 288957 } else { 3969823589 });
 }
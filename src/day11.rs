@@ -1,3 +1,5 @@
+use crate::animate::Animation;
+use crate::repl;
 use crate::utils::*;
 
 fn step(grid: &mut Grid<u8>) -> usize {
@@ -15,18 +17,10 @@ fn step(grid: &mut Grid<u8>) -> usize {
     while i < toflash.len() {
         let (r, c) = toflash[i];
 
-        let r_lo = if r == 0 { 0 } else { r - 1 };
-        let r_hi = if r + 1 == grid.rows { r } else { r + 1 };
-        let c_lo = if c == 0 { 0 } else { c - 1 };
-        let c_hi = if c + 1 == grid.cols { c } else { c + 1 };
-        for xr in r_lo..=r_hi {
-            for xc in c_lo..=c_hi {
-                if xr == r && xc == c { continue; }
-
-                grid[(xr, xc)] += 1;
-                if grid[(xr, xc)] == 10 {
-                    toflash.push((xr, xc));
-                }
+        for (xr, xc) in grid.adjacent8(r, c) {
+            grid[(xr, xc)] += 1;
+            if grid[(xr, xc)] == 10 {
+                toflash.push((xr, xc));
             }
         }
         i += 1;
@@ -40,6 +34,108 @@ fn step(grid: &mut Grid<u8>) -> usize {
     flashes
 }
 
+fn parse_grid(input_str: &str) -> Grid<u8> {
+    Grid::parse(input_str, |ch| ch - b'0').unwrap()
+}
+
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let mut grid = parse_grid(input_str);
+
+    let mut flashes_100 = 0;
+    let mut first_sync = 0;
+    for s in 1..1000 {
+        let step_flashes = step(&mut grid);
+        if s <= 100 {
+            flashes_100 += step_flashes;
+        }
+
+        if step_flashes == grid.rows * grid.cols {
+            first_sync = s;
+            break;
+        }
+    }
+
+    let part1 = flashes_100;
+    let part2 = first_sync;
+
+    (part1, part2)
+}
+
+// Energy levels as digits, with octopuses that just flashed (energy 0) drawn as `flashed`.
+fn energy_map(grid: &Grid<u8>, flashed: u8) -> Grid<u8> {
+    let mut shown = grid.clone();
+    for value in shown.data.iter_mut() {
+        *value = if *value == 0 { flashed } else { *value + b'0' };
+    }
+    shown
+}
+
+fn energy_palette(ch: char) -> Option<&'static str> {
+    match ch {
+        '*' => Some("1;93"),
+        '1'..='4' => Some("90"),
+        '5'..='7' => Some("34"),
+        '8' | '9' => Some("36"),
+        _ => None,
+    }
+}
+
+pub fn animate(input_str: &str, anim: &mut Animation) {
+    let mut grid = parse_grid(input_str);
+    for s in 1..1000 {
+        let flashes = step(&mut grid);
+        anim.frame(&format!("Step {}: {} flashes", s, flashes), &energy_map(&grid, b'*'), energy_palette);
+        if flashes == grid.rows * grid.cols {
+            break;
+        }
+    }
+}
+
+pub struct OctopusSession {
+    grid: Grid<u8>,
+    steps: usize,
+    flashes: usize,
+}
+
+impl OctopusSession {
+    pub fn new(input_str: &str) -> Self {
+        OctopusSession{grid: parse_grid(input_str), steps: 0, flashes: 0}
+    }
+
+    // Octopuses that just flashed show up blank.
+    fn fmt_energy(&self) -> String {
+        energy_map(&self.grid, 0).fmt_map()
+    }
+}
+
+impl repl::Session for OctopusSession {
+    fn help(&self) -> &'static str {
+        "\
+step [N]            advance N steps (default 1) and show the grid
+show                show the grid, with flashed octopuses blank
+table               show the energy levels as a table, with flashes highlighted"
+    }
+
+    fn run(&mut self, cmd: &str, args: &[&str]) -> Result<String, String> {
+        match cmd {
+            "step" => {
+                let n: usize = repl::arg(args, 0, Some(1))?;
+                let mut flashed = 0;
+                for _ in 0..n {
+                    flashed += step(&mut self.grid);
+                }
+                self.steps += n;
+                self.flashes += flashed;
+                Ok(format!("After step {}: {} flashes ({} total)\n{}",
+                    self.steps, flashed, self.flashes, self.fmt_energy()))
+            }
+            "show" => Ok(self.fmt_energy()),
+            "table" => Ok(self.grid.fmt_table_styled(|_, energy| (*energy == 0).then_some("1;93"))),
+            _ => Err(repl::unknown(cmd)),
+        }
+    }
+}
+
 pub fn day11(test_mode: bool, print: bool) {
     const INPUT: &str = "inputs/input11.txt";
     let file_str = std::fs::read_to_string(INPUT).unwrap();
@@ -58,40 +154,9 @@ pub fn day11(test_mode: bool, print: bool) {
         &file_str
     };
 
-    let mut grid_data = Vec::<u8>::with_capacity(10 * 10);
-    let mut grid_rows = 0usize;
-    let mut grid_cols = 0usize;
-    for line in input_str.lines() {
-        let line = line.trim_start();
-        grid_cols = line.len();
-        grid_rows += 1;
-
-        for ch in line.bytes() {
-            grid_data.push(ch - b'0');
-        }
-    }
-
-    let mut grid = Grid{rows: grid_rows, cols: grid_cols, data: grid_data};
-
-    let mut flashes_100 = 0;
-    let mut first_sync = 0;
-    for s in 1..1000 {
-        let step_flashes = step(&mut grid);
-        if s <= 100 {
-            flashes_100 += step_flashes;
-        }
-
-        if step_flashes == grid.rows * grid.cols {
-            first_sync = s;
-            break;
-        }
-    }
-
-    let part1 = flashes_100;
+    let (part1, part2) = solve(input_str);
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 1656 } else { 1617 });
-
-    let part2 = first_sync;
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 195 } else { 258 });
 }
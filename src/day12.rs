@@ -1,15 +1,21 @@
 use rustc_hash::FxHashMap;
 
+use crate::utils::*;
+
 type Graph = Vec<Vec<usize>>;
 
-fn count_paths(edges: &Graph, is_small: &[bool], seen: &mut [bool], at: usize, small_repeats_left: usize) -> usize {
+// (cave, small caves visited so far as a bitmask, repeat visits left)
+type PathState = (usize, u64, usize);
+
+// The number of ways to the end from `at`, having already visited `seen`.
+fn count_paths(memo: &mut Memo<PathState, usize>, edges: &Graph, is_small: &[bool], at: usize, seen: u64, small_repeats_left: usize) -> usize {
     if at == 1 {  // The end
         return 1;
     }
 
     let is_lowercase = is_small[at];
 
-    let is_repeat = is_lowercase && seen[at];
+    let is_repeat = is_lowercase && seen & (1 << at) != 0;
     let mut small_repeats_left = small_repeats_left;
     if is_repeat {
         if at == 0 || small_repeats_left == 0 {
@@ -18,49 +24,17 @@ fn count_paths(edges: &Graph, is_small: &[bool], seen: &mut [bool], at: usize, s
         small_repeats_left -= 1;
     }
 
-    if is_lowercase && !is_repeat {
-        seen[at] = true;
-    }
-
-    let mut paths = 0;
-    for next in &edges[at] {
-        paths += count_paths(edges, is_small, seen, *next, small_repeats_left);
-    }
-
-    if is_lowercase && !is_repeat {
-        seen[at] = false;
-    }
+    let seen = if is_lowercase { seen | 1 << at } else { seen };
 
-    paths
+    // Where the path goes from here only depends on what it has used up so far.
+    memo.get_or_compute((at, seen, small_repeats_left), |memo| {
+        edges[at].iter()
+            .map(|next| count_paths(memo, edges, is_small, *next, seen, small_repeats_left))
+            .sum()
+    })
 }
 
-
-pub fn day12(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input12.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        "fs-end
-he-DX
-fs-he
-start-DX
-pj-DX
-end-zg
-zg-sl
-zg-pj
-pj-he
-RW-he
-fs-DX
-pj-RW
-zg-RW
-start-pj
-he-WI
-zg-he
-pj-fs
-start-RW"
-    } else {
-        &file_str
-    };
-
+pub fn solve(input_str: &str) -> (usize, usize) {
     let mut node_lookup = FxHashMap::<String, usize>::with_capacity_and_hasher(100, Default::default());
     node_lookup.insert("start".to_string(), 0);
     node_lookup.insert("end".to_string(), 1);
@@ -91,13 +65,43 @@ start-RW"
         is_small[v] = k.as_bytes().first().unwrap() >= &b'a';
     }
 
-    let mut seen = vec![false; edges.len()];
-    let part1 = count_paths(&edges, &is_small, &mut seen, 0, 0);
-    let part2 = count_paths(&edges, &is_small, &mut seen, 0, 1);
+    assert!(edges.len() <= 64, "Too many caves for the visited bitmask");
+    let mut memo = Memo::new();
+    let part1 = count_paths(&mut memo, &edges, &is_small, 0, 0, 0);
+    let part2 = count_paths(&mut memo, &edges, &is_small, 0, 0, 1);
+
+    (part1, part2)
+}
 
+pub fn day12(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input12.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        "fs-end
+he-DX
+fs-he
+start-DX
+pj-DX
+end-zg
+zg-sl
+zg-pj
+pj-he
+RW-he
+fs-DX
+pj-RW
+zg-RW
+start-pj
+he-WI
+zg-he
+pj-fs
+start-RW"
+    } else {
+        &file_str
+    };
+
+    let (part1, part2) = solve(input_str);
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 226 } else { 3369 });
-
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 3509 } else { 85883 });
 }
@@ -1,7 +1,91 @@
+use crate::animate::Animation;
 use crate::utils::*;
 
-use rustc_hash::FxHashSet;
 
+// Returns the dots, and the folds as (is_x, location).
+fn parse(input_str: &str) -> (SparseGrid<u8>, Vec<(bool, i32)>) {
+    let mut scan = ByteScanner::new(input_str);
+    let mut dots = Vec::<Pt>::with_capacity(1000);
+    while let Some(x) = scan.int() {
+        scan.tag(",").unwrap();
+        let y = scan.int().unwrap();
+        scan.skip_whitespace();
+        dots.push(Pt{x, y});
+    }
+
+    // "fold along x=5"
+    let mut foldings = Vec::<(bool, i32)>::with_capacity(10);  // (is_x, location)
+    while scan.tag("fold along ").is_some() {
+        let var = scan.byte().unwrap();
+        scan.tag("=").unwrap();
+        let value = scan.int().unwrap();
+        scan.skip_whitespace();
+        foldings.push((var == b'x', value));
+    }
+
+    let mut dots_showing = SparseGrid::with_capacity(0, dots.len());
+    for pt in dots {
+        dots_showing.set(pt, b'#');
+    }
+
+    (dots_showing, foldings)
+}
+
+fn fold_once(dots_showing: SparseGrid<u8>, is_x: bool, fold: i32) -> SparseGrid<u8> {
+    let mut next_dots = SparseGrid::with_capacity(0, dots_showing.len());
+    for &dot in dots_showing.points() {
+        if is_x {
+            if dot.x < fold {
+                next_dots.set(dot, b'#');
+            } else {
+                next_dots.set(Pt{x: 2 * fold - dot.x, y: dot.y}, b'#');
+            }
+        } else { // folded along y
+            if dot.y < fold {
+                next_dots.set(dot, b'#');
+            } else {
+                next_dots.set(Pt{x: dot.x, y: 2 * fold - dot.y}, b'#');
+            }
+        }
+    }
+    next_dots
+}
+
+// Returns the number of dots after the first fold, and the dots after all folds.
+fn fold_dots(input_str: &str) -> (i32, SparseGrid<u8>) {
+    let (mut dots_showing, foldings) = parse(input_str);
+
+    let mut first_fold_dots = -1;
+    for (is_x, fold) in foldings {
+        dots_showing = fold_once(dots_showing, is_x, fold);
+
+        if first_fold_dots < 0 {
+            first_fold_dots = dots_showing.len() as i32;
+        }
+    }
+
+    (first_fold_dots, dots_showing)
+}
+
+fn dots_map(dots: &SparseGrid<u8>) -> Grid<u8> {
+    dots.to_grid().1
+}
+
+pub fn animate(input_str: &str, anim: &mut Animation) {
+    let (mut dots_showing, foldings) = parse(input_str);
+    let palette = |ch| if ch == '#' { Some("1;92") } else { None };
+    anim.frame(&format!("{} dots", dots_showing.len()), &dots_map(&dots_showing), palette);
+    for (is_x, fold) in foldings {
+        dots_showing = fold_once(dots_showing, is_x, fold);
+        let title = format!("Folded along {}={}: {} dots", if is_x { 'x' } else { 'y' }, fold, dots_showing.len());
+        anim.frame(&title, &dots_map(&dots_showing), palette);
+    }
+}
+
+pub fn solve(input_str: &str) -> (i32, String) {
+    let (first_fold_dots, dots_showing) = fold_dots(input_str);
+    (first_fold_dots, ocr_pts(dots_showing.points()).text)
+}
 
 pub fn day13(test_mode: bool, print: bool) {
     const INPUT: &str = "inputs/input13.txt";
@@ -32,83 +116,22 @@ fold along x=5"
         &file_str
     };
 
-    let mut dots = Vec::<Pt>::with_capacity(1000);
-    let mut foldings = Vec::<(bool, i32)>::with_capacity(10);  // (is_x, location)
-    let mut folding_step = false;
-    for line in input_str.lines() {
-
-        if !folding_step {
-            if line.is_empty() {
-                folding_step = true;
-            } else {
-                let mut it = line.split(",");
-                let x = it.next().unwrap().parse().unwrap();
-                let y = it.next().unwrap().parse().unwrap();
-                dots.push(Pt{x, y});
-            }
-        } else {
-            let mut words = line.split(' ');
-            let _ = words.next();
-            let _ = words.next();
-            let mut expr = words.next().unwrap().split("=");
-            let var = expr.next().unwrap();
-            let value = expr.next().unwrap().parse().unwrap();
-            foldings.push((var == "x", value));
-        }
-    }
-
-    let mut dots_showing = FxHashSet::<Pt>::with_capacity_and_hasher(dots.len(), Default::default());
-    for pt in dots {
-        dots_showing.insert(pt);
-    }
-
-    let mut first_fold_dots = -1;
-    for (is_x, fold) in foldings {
-        let mut next_dots = FxHashSet::<Pt>::with_capacity_and_hasher(dots_showing.len(), Default::default());
-        for dot in dots_showing {
-            if is_x {
-                if dot.x < fold {
-                    next_dots.insert(dot);
-                } else {
-                    next_dots.insert(Pt{x: 2 * fold - dot.x, y: dot.y});
-                }
-            } else { // folded along y
-                if dot.y < fold {
-                    next_dots.insert(dot);
-                } else {
-                    next_dots.insert(Pt{x: dot.x, y: 2 * fold - dot.y});
-                }
-            }
-        }
-        dots_showing = next_dots;
-
-        if first_fold_dots < 0 {
-            first_fold_dots = dots_showing.len() as i32;
-        }
-    }
-
+    let (first_fold_dots, dots_showing) = fold_dots(input_str);
 
     let part1 = first_fold_dots; // dots_showing.len();
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 17 } else { 720 });
 
+    let letters = ocr_pts(dots_showing.points());
     if print {
-        let mut out = String::with_capacity(1000);
-        for y in 0..100 {
-            for x in 0..100 {
-                if dots_showing.contains(&Pt{x: x, y: y}) {
-                    out += "#";
-                } else {
-                    out += " ";
-                }
-            }
-            out += "\n";
+        println!("{}", dots_showing.fmt_map());
+        for (pos, glyph) in &letters.unrecognized {
+            println!("Unrecognized letter {}:\n{}", pos + 1, glyph);
         }
-        println!("{}", out);
     }
 
-    let part2 = dots_showing.len();
+    let part2 = letters.text;
     if print { println!("Part 2: {}", part2); }
-    assert_eq!(part2, if test_mode { 16 } else { 104 });
-    //AHPRPAUZ
+    // The example folds into a square, which isn't a letter.
+    assert_eq!(part2, if test_mode { "?" } else { "AHPRPAUZ" });
 }
@@ -1,68 +1,54 @@
-use std::collections::BinaryHeap;
+use std::io;
 
 use crate::utils::*;
 
 pub fn parse_digit_grid(string: &str) -> Grid<u8> {
-    let mut grid_data = Vec::<u8>::with_capacity(1024);
-    let mut grid_rows = 0usize;
-    let mut grid_cols = 0usize;
-    for line in string.lines() {
-        let line = line.trim_start();
-        grid_cols = line.len();
-        grid_rows += 1;
-
-        for ch in line.bytes() {
-            grid_data.push(ch - b'0');
-        }
-    }
-    assert_eq!(grid_data.len(), grid_rows * grid_cols);
+    Grid::parse(string, |ch| ch - b'0').unwrap()
+}
+
+
+// The least risky route from the top left to the bottom right. The path is only recorded
+// if the search keeps its paths.
+fn safest_route<H: History<Pt>>(grid: &Grid<u8>, search: AStar<Pt, GridVisited, H>) -> Route<Pt> {
+    let goal = Pt::at(grid.cols as i32 - 1, grid.rows as i32 - 1);
+    search.run(
+        |at| grid.adjacent4(at.y as usize, at.x as usize).map(|(r, c)| (Pt::at(c as i32, r as i32), grid[(r, c)] as usize)),
+        // Manhattan distance would be a valid heuristic, but all the ties make it slower.
+        |_| 0,
+        |at| *at == goal,
+    ).unwrap()
+}
+
+// A search from the top left, with the visited cells kept in a grid rather than hashed.
+fn from_top_left(grid: &Grid<u8>) -> AStar<Pt, GridVisited> {
+    AStar::with_visited(Pt::at(0, 0), GridVisited::new(grid.rows, grid.cols))
+}
 
-    Grid{rows: grid_rows, cols: grid_cols, data: grid_data}
+// The safest route itself, as (row, col) cells.
+fn find_safest_route(grid: &Grid<u8>) -> Vec<(usize, usize)> {
+    let route = safest_route(grid, from_top_left(grid).with_paths());
+    route.path.into_iter().map(|at| (at.y as usize, at.x as usize)).collect()
 }
 
+// The risk levels with the safest path picked out, for the terminal.
+fn fmt_route(grid: &Grid<u8>) -> String {
+    grid.fmt_table_highlighted(find_safest_route(grid), "1;31")
+}
 
-fn find_min_risk(grid: &Grid<u8>) -> usize {
-    let mut visited = Grid{rows: grid.rows, cols: grid.cols, data: vec![false; grid.data.len()]};
-
-    let mut heap = BinaryHeap::with_capacity(grid.rows + grid.cols);
-    heap.push( ByFirstRev((0i32, Pt{x: 0, y: 0})));
-    loop {
-        let ByFirstRev((score, at)) = heap.pop().unwrap();
-
-        if visited[&at] { continue; }
-        visited[&at] = true;
-
-        if (at.x as usize) == grid.cols - 1 && (at.y as usize) == grid.rows - 1 {
-            // FINISHED
-            return score as usize;
-        }
-
-        // Walks the neighbors
-        if at.x > 0 {
-            let n = Pt{x: at.x - 1, y: at.y};
-            if !visited[&n] {
-                heap.push(ByFirstRev((score + grid[&n] as i32, n)));
-            }
-        }
-        if at.y > 0 {
-            let n = Pt{x: at.x, y: at.y - 1};
-            if !visited[&n] {
-                heap.push(ByFirstRev((score + grid[&n] as i32, n)));
-            }
-        }
-        if (at.x as usize) < grid.cols - 1 {
-            let n = Pt{x: at.x + 1, y: at.y};
-            if !visited[&n] {
-                heap.push(ByFirstRev((score + grid[&n] as i32, n)));
-            }
-        }
-        if (at.y as usize) < grid.rows - 1 {
-            let n = Pt{x: at.x, y: at.y + 1};
-            if !visited[&n] {
-                heap.push(ByFirstRev((score + grid[&n] as i32, n)));
-            }
-        }
+fn risk_image(grid: &Grid<u8>) -> Grid<Rgb> {
+    let mut image = Grid{rows: grid.rows, cols: grid.cols, data: grid.data.iter().map(|risk| [risk * 25; 3]).collect()};
+    for rc in find_safest_route(grid) {
+        image[rc] = [255, 40, 40];
     }
+    image
+}
+
+// The risk levels in grayscale with the safest path in red, for both the small and the tiled map.
+pub fn export(input_str: &str, frames: &mut FrameWriter) -> io::Result<()> {
+    let grid = parse_digit_grid(input_str);
+    frames.ppm(&risk_image(&grid))?;
+    frames.ppm(&risk_image(&tile_grid(&grid)))?;
+    Ok(())
 }
 
 #[inline]
@@ -70,6 +56,24 @@ fn wrap(risk: u8) -> u8 {
     (risk - 1) % 9 + 1
 }
 
+fn tile_grid(grid: &Grid<u8>) -> Grid<u8> {
+    grid.tiled(5, 5, |tile_r, tile_c, risk| wrap(risk + (tile_r + tile_c) as u8))
+}
+
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let grid = parse_digit_grid(input_str);
+
+    let part1 = safest_route(&grid, from_top_left(&grid)).cost;
+
+    // Expand
+    let big_grid = tile_grid(&grid);
+    // println!("Big grid:\n\n{}", big_grid.fmt_compact());
+
+    let part2 = safest_route(&big_grid, from_top_left(&big_grid)).cost;
+
+    (part1, part2)
+}
+
 pub fn day15(test_mode: bool, print: bool) {
     const INPUT: &str = "inputs/input15.txt";
     let file_str = std::fs::read_to_string(INPUT).unwrap();
@@ -88,25 +92,11 @@ pub fn day15(test_mode: bool, print: bool) {
         &file_str
     };
 
-    let grid = parse_digit_grid(input_str);
-
-    let part1 = find_min_risk(&grid);
+    let (part1, part2) = solve(input_str);
+    // The example is small enough to show the route through it.
+    if print && test_mode { print!("{}", fmt_route(&parse_digit_grid(input_str))); }
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 40 } else { 458 });
-
-
-    // Expand
-
-    let mut big_grid = Grid::filled(grid.rows * 5, grid.cols * 5, 0u8);
-    for r in 0..big_grid.rows {
-        for c in 0..big_grid.cols {
-            let offset = (r / grid.rows + c / grid.cols) as u8;
-            big_grid[(r, c)] = wrap(grid[(r % grid.rows, c % grid.cols)] + offset);
-        }
-    }
-    // println!("Big grid:\n\n{}", big_grid.fmt_compact());
-
-    let part2 = find_min_risk(&big_grid);
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 315 } else { 2800 });
 }
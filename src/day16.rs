@@ -1,5 +1,8 @@
 use std::cmp::min;
 
+use crate::repl;
+use crate::utils::hex_digit;
+
 struct BitsSnarfer<'a> {
     data: &'a [u8],
     byte: usize,
@@ -19,7 +22,7 @@ fn getbits(value: u8, start: usize, length: usize) -> u8 {
 }
 
 impl<'a> BitsSnarfer<'a> {
-    fn new(data: &[u8]) -> BitsSnarfer {
+    fn new(data: &[u8]) -> BitsSnarfer<'_> {
         BitsSnarfer{data, byte: 0, bit: 0}
     }
 
@@ -51,12 +54,15 @@ impl<'a> BitsSnarfer<'a> {
     }
 }
 
-struct PacketResult {
+struct Packet {
+    version: usize,
+    type_id: usize,
     value: usize,
     sum_versions: usize,
+    subpackets: Vec<Packet>,
 }
 
-fn parse_packet(sn: &mut BitsSnarfer) -> PacketResult {
+fn parse_packet(sn: &mut BitsSnarfer) -> Packet {
     let version = sn.snarf(3);
     let type_id = sn.snarf(3);
     // println!("version: {}, type_id: {}", version, type_id);
@@ -72,7 +78,7 @@ fn parse_packet(sn: &mut BitsSnarfer) -> PacketResult {
                 break;
             }
         }
-        PacketResult{ value, sum_versions: version }
+        Packet{ version, type_id, value, sum_versions: version, subpackets: Vec::new() }
     } else { // Operator
         let length_type_id = sn.snarf(1);
         let (subpacket_bits, subpacket_count) =
@@ -83,26 +89,120 @@ fn parse_packet(sn: &mut BitsSnarfer) -> PacketResult {
             };
 
         let mut sum_versions = version;
-        let mut subvalues = Vec::<usize>::with_capacity(8);
+        let mut subpackets = Vec::<Packet>::with_capacity(8);
         let subpackets_start_loc = sn.loc();
-        while subvalues.len() < subpacket_count && sn.bits_since(&subpackets_start_loc) < subpacket_bits {
+        while subpackets.len() < subpacket_count && sn.bits_since(&subpackets_start_loc) < subpacket_bits {
             let subpacket = parse_packet(sn);
             sum_versions += subpacket.sum_versions;
-            subvalues.push(subpacket.value);
+            subpackets.push(subpacket);
         }
 
+        let subvalue = |i: usize| subpackets[i].value;
         let value: usize = match type_id {
-            0 => subvalues.iter().sum(),
-            1 => subvalues.iter().product(),
-            2 => *subvalues.iter().min().unwrap(),
-            3 => *subvalues.iter().max().unwrap(),
-            5 => if subvalues[0] > subvalues[1] { 1 } else { 0 },
-            6 => if subvalues[0] < subvalues[1] { 1 } else { 0 },
-            7 => if subvalues[0] == subvalues[1] { 1 } else { 0 },
+            0 => subpackets.iter().map(|p| p.value).sum(),
+            1 => subpackets.iter().map(|p| p.value).product(),
+            2 => subpackets.iter().map(|p| p.value).min().unwrap(),
+            3 => subpackets.iter().map(|p| p.value).max().unwrap(),
+            5 => if subvalue(0) > subvalue(1) { 1 } else { 0 },
+            6 => if subvalue(0) < subvalue(1) { 1 } else { 0 },
+            7 => if subvalue(0) == subvalue(1) { 1 } else { 0 },
             _ => unimplemented!(),
         };
 
-        PacketResult{ value, sum_versions }
+        Packet{ version, type_id, value, sum_versions, subpackets }
+    }
+}
+
+fn parse_transmission(hex: &str) -> Packet {
+    let data: Vec<u8> = hex.as_bytes()
+        .chunks(2)
+        .map(|pair| hex_digit(pair[0]).unwrap() << 4 | hex_digit(pair[1]).unwrap())
+        .collect();
+
+    let mut sn = BitsSnarfer::new(&data);
+    parse_packet(&mut sn)
+}
+
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let input_str = input_str.trim_end();
+
+    let parsed = parse_transmission(input_str);
+
+    let part1 = parsed.sum_versions;
+    let part2 = parsed.value;
+
+    (part1, part2)
+}
+
+const OPERATOR_NAMES: [&str; 8] = ["sum", "product", "min", "max", "literal", "gt", "lt", "eq"];
+
+fn fmt_tree(packet: &Packet, depth: usize, max_depth: usize, out: &mut String) {
+    out.push_str(&format!("{}{} v{} = {}",
+        "  ".repeat(depth), OPERATOR_NAMES[packet.type_id], packet.version, packet.value));
+    if !packet.subpackets.is_empty() && depth == max_depth {
+        out.push_str(&format!("  ({} subpackets)", packet.subpackets.len()));
+    }
+    out.push('\n');
+    if depth < max_depth {
+        for sub in &packet.subpackets {
+            fmt_tree(sub, depth + 1, max_depth, out);
+        }
+    }
+}
+
+pub struct PacketSession {
+    root: Packet,
+}
+
+impl PacketSession {
+    pub fn new(input_str: &str) -> Self {
+        PacketSession{root: parse_transmission(input_str.trim_end())}
+    }
+
+    // Follows subpacket indices down from the root.
+    fn find(&self, path: &[&str]) -> Result<&Packet, String> {
+        let mut packet = &self.root;
+        for (i, step) in path.iter().enumerate() {
+            let idx: usize = repl::arg(path, i, None)?;
+            packet = packet.subpackets.get(idx)
+                .ok_or_else(|| format!("No subpacket {} at {}", idx, step))?;
+        }
+        Ok(packet)
+    }
+}
+
+impl repl::Session for PacketSession {
+    fn help(&self) -> &'static str {
+        "\
+eval [I J ...]      evaluate the packet at that subpacket path (root by default)
+tree [DEPTH]        show the packet tree down to DEPTH (default 2)
+load HEX            replace the transmission, e.g. with an example"
+    }
+
+    fn run(&mut self, cmd: &str, args: &[&str]) -> Result<String, String> {
+        match cmd {
+            "eval" => {
+                let packet = self.find(args)?;
+                Ok(format!("{} v{} = {}  (version sum {}, {} subpackets)",
+                    OPERATOR_NAMES[packet.type_id], packet.version, packet.value,
+                    packet.sum_versions, packet.subpackets.len()))
+            }
+            "tree" => {
+                let max_depth: usize = repl::arg(args, 0, Some(2))?;
+                let mut out = String::new();
+                fmt_tree(&self.root, 0, max_depth, &mut out);
+                Ok(out)
+            }
+            "load" => {
+                let hex: String = repl::arg(args, 0, None)?;
+                if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    return Err(format!("Not a hex transmission: {}", hex));
+                }
+                self.root = parse_transmission(&hex);
+                Ok(format!("{} v{} = {}", OPERATOR_NAMES[self.root.type_id], self.root.version, self.root.value))
+            }
+            _ => Err(repl::unknown(cmd)),
+        }
     }
 }
 
@@ -112,23 +212,12 @@ pub fn day16(test_mode: bool, print: bool) {
     let input_str = if test_mode {
         "9C0141080250320F1802104A08"
     } else {
-        &file_str.trim_end()
+        &file_str
     };
 
-    let data: Vec<u8> = input_str.as_bytes()
-        .chunks(2)
-        .map(|pair| u8::from_str_radix(&String::from_utf8_lossy(pair), 16).unwrap())
-        .collect();
-
-    let mut sn = BitsSnarfer::new(&data);
-    let parsed = parse_packet(&mut sn);
-
-    let part1 = parsed.sum_versions;
+    let (part1, part2) = solve(input_str);
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 20 } else { 925 });
-
-
-    let part2 = parsed.value;
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 1 } else { 342997120375 });
 }
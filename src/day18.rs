@@ -27,7 +27,7 @@ where It: Iterator<Item=char>
     let mut num_str = String::with_capacity(8);
     loop {
         let ch = *it.peek().unwrap();
-        if ch < '0' || ch > '9' {
+        if !ch.is_ascii_digit() {
             break;
         }
 
@@ -175,7 +175,7 @@ fn try_split(snail: &mut SnailNum) -> bool {
             }
         }
     }
-    return false;
+    false
 }
 
 fn reduce(mut snail: SnailNum) -> SnailNum {
@@ -214,6 +214,28 @@ fn mag(snail: &SnailNum) -> i32 {
     3 * mag_piece(&snail.0) + 2 * mag_piece(&snail.1)
 }
 
+pub fn solve(input_str: &str) -> (i32, i32) {
+    let input_str = input_str.trim_end();
+
+    let snails: Vec<SnailNum> = input_str.lines().map(parse_line).collect();
+    let sum = snails.clone().into_iter().reduce(add).unwrap();
+    // println!("Sum: {}", psnail(&sum));
+
+    let part1 = mag(&sum);
+
+    let mut best_mag = 0;
+    for i in 0..snails.len() {
+        for j in 0..snails.len() {
+            if i == j { continue; }
+            best_mag = max(best_mag, mag(&add(snails[i].clone(), snails[j].clone())));
+        }
+    }
+
+    let part2 = best_mag;
+
+    (part1, part2)
+}
+
 pub fn day18(test_mode: bool, print: bool) {
     const INPUT: &str = "inputs/input18.txt";
     let file_str = std::fs::read_to_string(INPUT).unwrap();
@@ -229,28 +251,12 @@ pub fn day18(test_mode: bool, print: bool) {
 [[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
 [[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]"
     } else {
-        &file_str.trim_end()
+        &file_str
     };
 
-    let snails: Vec<SnailNum> = input_str.lines().map(|line| parse_line(line)).collect();
-    let sum = snails.clone().into_iter().reduce(|a, b| add(a, b)).unwrap();
-    // println!("Sum: {}", psnail(&sum));
-
-
-
-    let part1 = mag(&sum);
+    let (part1, part2) = solve(input_str);
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 4140 } else { 3987 });
-
-    let mut best_mag = 0;
-    for i in 0..snails.len() {
-        for j in 0..snails.len() {
-            if i == j { continue; }
-            best_mag = max(best_mag, mag(&add(snails[i].clone(), snails[j].clone())));
-        }
-    }
-
-    let part2 = best_mag;
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 3993 } else { 4500 });
 }
@@ -320,8 +326,8 @@ mod test {
 [2,2]
 [3,3]
 [4,4]"
-            .lines().map(|line| parse_line(line)).into_iter()
-            .reduce(|a, b| add(a, b)).unwrap();
+            .lines().map(parse_line)
+            .reduce(add).unwrap();
         assert_eq!(result, parse_line("[[[[1,1],[2,2]],[3,3]],[4,4]]"));
 
         let result = "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]
@@ -334,8 +340,8 @@ mod test {
 [1,[[[9,3],9],[[9,0],[0,7]]]]
 [[[5,[7,4]],7],1]
 [[[[4,2],2],6],[8,7]]"
-            .lines().map(|line| parse_line(line)).into_iter()
-            .reduce(|a, b| add(a, b)).unwrap();
+            .lines().map(parse_line)
+            .reduce(add).unwrap();
         assert_eq!(result, parse_line("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]"));     
     }
 
@@ -33,13 +33,14 @@ fn build_scanner(beacons: Vec<Pt3>) -> Scanner {
 
     for i in 0..beacons.len() {
         for j in 0..beacons.len() {
-            dist_sqrs[(i, j)] = beacons[i].dist_to_sqr(&beacons[j]);
+            dist_sqrs[(i, j)] = beacons[i].dist_sqr(beacons[j]);
         }
     }
 
     Scanner{beacons, dist_sqrs}
 }
 
+#[allow(clippy::needless_range_loop)]
 fn try_complete_match(scanner_a: &Scanner, scanner_b: &Scanner, mut matches: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
     matches.reserve(16);
 
@@ -77,181 +78,41 @@ fn try_complete_match(scanner_a: &Scanner, scanner_b: &Scanner, mut matches: Vec
     matches
 }
 
-// u = R * v
-fn rotation_for(u: &Pt3, v: &Pt3) -> Option<Grid<i32>> {
-    if u.x.abs() == u.y.abs() || u.x.abs() == u.z.abs() || u.y.abs() == u.z.abs() {
-        // Ambiguous. Could get confused.
-        return None;
-    }
-
-    let mut rot = Grid::filled(3, 3, 0);
-
-    if u.x == v.x {
-        rot[(0, 0)] = 1;
-    } else if u.x == -v.x {
-        rot[(0, 0)] = -1;
-    } else if u.x == v.y {
-        rot[(0, 1)] = 1;
-    } else if u.x == -v.y {
-        rot[(0, 1)] = -1;
-    } else if u.x == v.z {
-        rot[(0, 2)] = 1;
-    } else if u.x == -v.z {
-        rot[(0, 2)] = -1;
-    } else {
-        panic!();
-    }
-
-    if u.y == v.x {
-        rot[(1, 0)] = 1;
-    } else if u.y == -v.x {
-        rot[(1, 0)] = -1;
-    } else if u.y == v.y {
-        rot[(1, 1)] = 1;
-    } else if u.y == -v.y {
-        rot[(1, 1)] = -1;
-    } else if u.y == v.z {
-        rot[(1, 2)] = 1;
-    } else if u.y == -v.z {
-        rot[(1, 2)] = -1;
-    } else {
-        panic!();
-    }
-
-    if u.z == v.x {
-        rot[(2, 0)] = 1;
-    } else if u.z == -v.x {
-        rot[(2, 0)] = -1;
-    } else if u.z == v.y {
-        rot[(2, 1)] = 1;
-    } else if u.z == -v.y {
-        rot[(2, 1)] = -1;
-    } else if u.z == v.z {
-        rot[(2, 2)] = 1;
-    } else if u.z == -v.z {
-        rot[(2, 2)] = -1;
-    } else {
-        panic!();
-    }
-
-    Some(rot)
-}
-
-#[derive(Clone)]
-struct Transform {
-    t: Pt3,
-    rot: Grid<i32>,
-}
-
-impl Transform {
-    fn ident() -> Self {
-        let mut tr = Transform{t: Pt3::new(0,0,0), rot: Grid::filled(3, 3, 0)};
-        tr.rot[(0,0)] = 1;
-        tr.rot[(1,1)] = 1;
-        tr.rot[(2,2)] = 1;
-        tr
-    }
-
-    fn fwd(&self, pt: &Pt3) -> Pt3 {
-        let x = self.t.x + self.rot[(0,0)] * pt.x + self.rot[(0,1)] * pt.y + self.rot[(0,2)] * pt.z;
-        let y = self.t.y + self.rot[(1,0)] * pt.x + self.rot[(1,1)] * pt.y + self.rot[(1,2)] * pt.z;
-        let z = self.t.z + self.rot[(2,0)] * pt.x + self.rot[(2,1)] * pt.y + self.rot[(2,2)] * pt.z;
-        Pt3{x, y, z}
-    }
-
-    fn inv(&self) -> Transform {
-        let mut invrot = Grid::filled(3, 3, 0);
-        for r in 0..3 {
-            for c in 0..3 {
-                invrot[(r, c)] = self.rot[(c, r)];  // Transpose
-            }
-        }
-
-        // Hacky way of inverting the translation.
-        let fwdpt = self.fwd(&Pt3::new(0,0,0));
-        let mut inverted = Transform{t: Pt3::new(0,0,0), rot: invrot};
-        let resultpt = inverted.fwd(&fwdpt);
-        inverted.t.x = -resultpt.x;
-        inverted.t.y = -resultpt.y;
-        inverted.t.z = -resultpt.z;
-        
-        inverted
-    }
-
-    fn chain(&self, rhs: &Transform) -> Transform {
-        let rot = Grid::filled(3, 3, 0);
-        let mut tr = Transform{t: Pt3::new(0, 0, 0), rot};
-
-        tr.rot[(0,0)] = self.rot[(0,0)] * rhs.rot[(0,0)] + self.rot[(0,1)] * rhs.rot[(1,0)] + self.rot[(0,2)] * rhs.rot[(2,0)];
-        tr.rot[(0,1)] = self.rot[(0,0)] * rhs.rot[(0,1)] + self.rot[(0,1)] * rhs.rot[(1,1)] + self.rot[(0,2)] * rhs.rot[(2,1)];
-        tr.rot[(0,2)] = self.rot[(0,0)] * rhs.rot[(0,2)] + self.rot[(0,1)] * rhs.rot[(1,2)] + self.rot[(0,2)] * rhs.rot[(2,2)];
-
-        tr.rot[(1,0)] = self.rot[(1,0)] * rhs.rot[(0,0)] + self.rot[(1,1)] * rhs.rot[(1,0)] + self.rot[(1,2)] * rhs.rot[(2,0)];
-        tr.rot[(1,1)] = self.rot[(1,0)] * rhs.rot[(0,1)] + self.rot[(1,1)] * rhs.rot[(1,1)] + self.rot[(1,2)] * rhs.rot[(2,1)];
-        tr.rot[(1,2)] = self.rot[(1,0)] * rhs.rot[(0,2)] + self.rot[(1,1)] * rhs.rot[(1,2)] + self.rot[(1,2)] * rhs.rot[(2,2)];
-
-        tr.rot[(2,0)] = self.rot[(2,0)] * rhs.rot[(0,0)] + self.rot[(2,1)] * rhs.rot[(1,0)] + self.rot[(2,2)] * rhs.rot[(2,0)];
-        tr.rot[(2,1)] = self.rot[(2,0)] * rhs.rot[(0,1)] + self.rot[(2,1)] * rhs.rot[(1,1)] + self.rot[(2,2)] * rhs.rot[(2,1)];
-        tr.rot[(2,2)] = self.rot[(2,0)] * rhs.rot[(0,2)] + self.rot[(2,1)] * rhs.rot[(1,2)] + self.rot[(2,2)] * rhs.rot[(2,2)];
-
-        tr.t.x = self.rot[(0,0)] * rhs.t.x + self.rot[(0,1)] * rhs.t.y + self.rot[(0,2)] * rhs.t.z + self.t.x;
-        tr.t.y = self.rot[(1,0)] * rhs.t.x + self.rot[(1,1)] * rhs.t.y + self.rot[(1,2)] * rhs.t.z + self.t.y;
-        tr.t.z = self.rot[(2,0)] * rhs.t.x + self.rot[(2,1)] * rhs.t.y + self.rot[(2,2)] * rhs.t.z + self.t.z;
-
-        tr
-    }
-}
-
-impl std::fmt::Debug for Transform {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}\n{}", self.t, self.rot.fmt_table())
-    }
-}
-
 // a = T * b
-fn solve_scanner_match(a: &Scanner, b: &Scanner, matches: &Vec<(usize, usize)>) -> Transform {
+fn solve_scanner_match(a: &Scanner, b: &Scanner, matches: &[(usize, usize)]) -> RigidTransform {
     for j in 1..matches.len() {
-        let va = &a.beacons[matches[j].0] - &a.beacons[matches[0].0];
-        let vb = &b.beacons[matches[j].1] - &b.beacons[matches[0].1];
-
-        if let Some(rot) = rotation_for(&va, &vb) {
-            // Hacky way of getting the translation.
-            let rot_only = Transform{t: Pt3::new(0,0,0), rot: rot.clone()};
-            let t = &a.beacons[matches[0].0] - &rot_only.fwd(&b.beacons[matches[0].1]);
-            return Transform{t, rot};
+        let va = a.beacons[matches[j].0] - a.beacons[matches[0].0];
+        let vb = b.beacons[matches[j].1] - b.beacons[matches[0].1];
+
+        // Offsets with repeated components are ambiguous, so those get skipped.
+        if let Some(rot) = Rotation::aligning(vb, va) {
+            let shift = a.beacons[matches[0].0] - rot.apply(b.beacons[matches[0].1]);
+            return RigidTransform{rot, shift};
         }
     }
 
     panic!("No match found");
 }
 
-pub fn day19(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input19.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        TEST_EXAMPLE
-    } else {
-        &file_str.trim_end()
-    };
+pub fn solve(input_str: &str) -> (usize, i32) {
+    let input_str = input_str.trim_end();
 
     let scanners = {
         let mut scanners = Vec::<Scanner>::with_capacity(20);
-        let mut beacons = Vec::<Pt3>::with_capacity(30);
-
-        for line in input_str.lines() {
-            if line.is_empty() {
-                // End of this beacon
-                scanners.push(build_scanner(beacons));
-                beacons = Vec::with_capacity(30);
-            } else if line.starts_with("---") {
-                // Scanner id line
-            } else {
-                let pos: Vec<i32> = line.split(',')
-                    .map(|n| n.parse().unwrap()).collect();
-                beacons.push(Pt3::new(pos[0], pos[1], pos[2]));
+        let mut scan = ByteScanner::new(input_str);
+
+        // "--- scanner 0 ---", then one "x,y,z" line per beacon.
+        while scan.tag("--- scanner ").is_some() {
+            scan.line();
+            let mut beacons = Vec::<Pt3>::with_capacity(30);
+            while let Some(x) = scan.int() {
+                scan.tag(",").unwrap();
+                let y = scan.int().unwrap();
+                scan.tag(",").unwrap();
+                let z = scan.int().unwrap();
+                scan.skip_whitespace();
+                beacons.push(Pt3::new(x, y, z));
             }
-        }
-        if !beacons.is_empty() {
             scanners.push(build_scanner(beacons));
         }
         scanners
@@ -276,7 +137,7 @@ pub fn day19(test_mode: bool, print: bool) {
     // (scanner A, B) -> num_matches
     let mut scanner_matches = Grid::filled(scanners.len(), scanners.len(), 0usize);
     // (A, B, [(beacon A, beacon B)], Transform(A <- B))
-    let mut all_matches = Vec::<(usize, usize, Vec<(usize, usize)>, Transform)>::new();
+    let mut all_matches = Vec::<(usize, usize, Vec<(usize, usize)>, RigidTransform)>::new();
     for starter_list in lookup_distsqr.values() {
         if starter_list.len() == 1 { continue; }
 
@@ -314,15 +175,22 @@ pub fn day19(test_mode: bool, print: bool) {
         }
     }
 
+    // The traversal below would never finish if some scanners can't be placed.
+    let mut clusters = DisjointSet::new(scanners.len());
+    for m in &all_matches {
+        clusters.union(m.0, m.1);
+    }
+    assert_eq!(clusters.num_components(), 1, "Scanners don't all overlap: {:?}", clusters.components());
+
     // A very bad topological traversal of the scanners
-    let mut solved: Vec<Option<Transform>> = vec![None; scanners.len()];
-    solved[0] = Some(Transform::ident());
+    let mut solved: Vec<Option<RigidTransform>> = vec![None; scanners.len()];
+    solved[0] = Some(RigidTransform::IDENTITY);
     'outer: loop {
         for m in &all_matches {
             if solved[m.0].is_some() && solved[m.1].is_none() {
-                solved[m.1] = Some(solved[m.0].as_ref().unwrap().chain(&m.3));
+                solved[m.1] = Some(solved[m.0].unwrap().compose(m.3));
             } else if solved[m.0].is_none() && solved[m.1].is_some() {
-                solved[m.0] = Some(solved[m.1].as_ref().unwrap().chain(&m.3.inv()));
+                solved[m.0] = Some(solved[m.1].unwrap().compose(m.3.inverse()));
             }
         }
 
@@ -337,31 +205,45 @@ pub fn day19(test_mode: bool, print: bool) {
 
     let mut all_beacons = FxHashSet::<Pt3>::with_capacity_and_hasher(30 * scanners.len(), Default::default());
     for i in 0..scanners.len() {
-        let tr = solved[i].as_ref().unwrap();
+        let tr = solved[i].unwrap();
 
-        for beacon_rel in &scanners[i].beacons {
-            let beacon_abs = tr.fwd(beacon_rel);
+        for &beacon_rel in &scanners[i].beacons {
+            let beacon_abs = tr.apply(beacon_rel);
             all_beacons.insert(beacon_abs);
         }
     }
 
     let part1 = all_beacons.len();
-    if print { println!("Part 1: {}", part1); }
-    assert_eq!(part1, if test_mode { 79 } else { 436 });
 
     let mut max_dist = 0;
     for i in 0..scanners.len() {
         for j in (i+1)..scanners.len() {
-            max_dist = max(max_dist, solved[i].as_ref().unwrap().t.l1_to(&solved[j].as_ref().unwrap().t));
+            max_dist = max(max_dist, solved[i].unwrap().shift.manhattan(solved[j].unwrap().shift));
         }
     }
 
     let part2 = max_dist;
+
+    (part1, part2)
+}
+
+pub fn day19(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input19.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        TEST_EXAMPLE
+    } else {
+        &file_str
+    };
+
+    let (part1, part2) = solve(input_str);
+    if print { println!("Part 1: {}", part1); }
+    assert_eq!(part1, if test_mode { 79 } else { 436 });
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 3621 } else { 10918 });
 }
 
-const TEST_EXAMPLE: &'static str = "--- scanner 0 ---
+const TEST_EXAMPLE: &str = "--- scanner 0 ---
 404,-588,-901
 528,-643,409
 -838,591,734
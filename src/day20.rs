@@ -1,117 +1,62 @@
-use crate::utils::*;
-
-// fn neighbors(r: i64, c: i64) -> [(i64, i64); 9] {
-//     [(r - 1, c - 1), (r - 1, c), (r - 1, c + 1),
-//      (r, c - 1), (r, c), (r, c + 1),
-//      (r + 1, c - 1), (r + 1, c), (r + 1, c + 1)]
-// }
+use std::io;
 
-fn enhance(lookup: &[u8], grid: &Grid<u8>, assume_outside: u8) -> Grid<u8> {
-    let mut data = Vec::<u8>::with_capacity((grid.rows + 2) * (grid.cols + 2));
+use crate::animate::Animation;
+use crate::utils::*;
 
-    for r in 0..(grid.rows + 2) {
-        for c in 0..(grid.cols + 2) {
+// Each enhancement grows the image by a pixel on every side. Everything further out is
+// `outside`, which flips every step if the lookup lights up an all-dark window.
+fn enhance(lookup: &[bool], image: &BitGrid, outside: bool) -> BitGrid {
+    image.map_windows3(1, outside, |index| lookup[index])
+}
 
+fn parse(input_str: &str) -> (Vec<bool>, BitGrid) {
+    let pixel = |ch| match ch { b'#' => true, b'.' => false, _ => unimplemented!() };
+    let (lookup, image) = input_str.split_once("\n\n").unwrap();
+    (lookup.trim().bytes().map(pixel).collect(), BitGrid::from_grid(&Grid::parse(image, pixel).unwrap(), |lit| *lit))
+}
 
-            // Filling result[r,c] from grid[r-1, c-1]
-            let mut index = 0usize;
+pub fn animate(input_str: &str, anim: &mut Animation) {
+    let (lookup, image) = parse(input_str);
+    let palette = |ch| if ch == '#' { Some("1;97") } else { None };
 
-            // Nicer, but slower by 1.5ms
-            //
-            // for (k, rc) in neighbors(r as i64, c as i64).iter().enumerate() {
-            //     if rc.0 - 1 >= 0 && rc.0 - 1 < (grid.rows as i64) && rc.1 - 1 >= 0 && rc.1 - 1 < (grid.cols as i64) {
-            //         index |= (grid[(rc.0 as usize - 1, rc.1 as usize - 1)] as usize) << (8 - k);
-            //     } else {
-            //         index |= (assume_outside as usize) << (8 - k);
-            //     }
-            // }
+    let mut current = image;
+    anim.frame("Input image", &current.to_grid(b'#', 0), palette);
+    for i in 0..50 {
+        current = enhance(&lookup, &current, lookup[0] && i % 2 == 1);
+        let title = format!("Enhanced {} times: {} lit", i + 1, current.count_ones());
+        anim.frame(&title, &current.to_grid(b'#', 0), palette);
+    }
+}
 
-            // Top row
-            if r > 1 {
-                if c > 1 {
-                    index |= (grid[(r - 2, c - 2)] as usize) << 8;
-                } else {
-                    index |= (assume_outside as usize) << 8;
-                }
-                if c > 0 && c - 1 < grid.cols {
-                    index |= (grid[(r - 2, c - 1)] as usize) << 7;
-                } else {
-                    index |= (assume_outside as usize) << 7;
-                }
-                if c < grid.cols {
-                    index |= (grid[(r - 2, c)] as usize) << 6;
-                } else {
-                    index |= (assume_outside as usize) << 6;
-                }
-            } else {
-                index |= (assume_outside as usize) << 8;
-                index |= (assume_outside as usize) << 7;
-                index |= (assume_outside as usize) << 6;
-            }
-            // Middle row
-            if r > 0 && r - 1 < grid.rows {
-                if c > 1 {
-                    index |= (grid[(r - 1, c - 2)] as usize) << 5;
-                } else {
-                    index |= (assume_outside as usize) << 5;
-                }
-                if c > 0 && c - 1 < grid.cols {
-                    index |= (grid[(r - 1, c - 1)] as usize) << 4;
-                } else {
-                    index |= (assume_outside as usize) << 4;
-                }
-                if c < grid.cols {
-                    index |= (grid[(r - 1, c)] as usize) << 3;
-                } else {
-                    index |= (assume_outside as usize) << 3;
-                }
-            } else {
-                index |= (assume_outside as usize) << 5;
-                index |= (assume_outside as usize) << 4;
-                index |= (assume_outside as usize) << 3;
-            }
-            // Bottom row
-            if r < grid.rows {
-                if c > 1 {
-                    index |= (grid[(r, c - 2)] as usize) << 2;
-                } else {
-                    index |= (assume_outside as usize) << 2;
-                }
-                if c > 0 && c - 1 < grid.cols {
-                    index |= (grid[(r, c - 1)] as usize) << 1;
-                } else {
-                    index |= (assume_outside as usize) << 1;
-                }
-                if c < grid.cols {
-                    index |= (grid[(r, c)] as usize) << 0;
-                } else {
-                    index |= (assume_outside as usize) << 0;
-                }
-            } else {
-                index |= (assume_outside as usize) << 2;
-                index |= (assume_outside as usize) << 1;
-                index |= (assume_outside as usize) << 0;
-            }
+// One frame per enhancement, all padded out to the final image size.
+pub fn export(input_str: &str, frames: &mut FrameWriter) -> io::Result<()> {
+    let (lookup, image) = parse(input_str);
 
-            data.push(lookup[index]);
+    let mut current = image;
+    for i in 0..=50 {
+        let outside = lookup[0] && i % 2 == 1;
+        frames.pgm(&current.to_grid(255, 0).padded(50 - i, if outside { 255 } else { 0 }))?;
+        if i < 50 {
+            current = enhance(&lookup, &current, outside);
         }
     }
-    Grid{rows: grid.rows + 2, cols: grid.cols + 2, data}
+    Ok(())
 }
 
-#[allow(dead_code)]
-fn pgrid(grid: &Grid<u8>) {
-    let mut ch = Grid::<char>::filled(grid.rows, grid.cols, ' ');
-    for r in 0..grid.rows {
-        for c in 0..grid.cols {
-            if grid[(r, c)] == 1 {
-                ch[(r, c)] = '#';
-            } else {
-                ch[(r, c)] = '.';
-            }
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let (lookup, image) = parse(input_str);
+
+    let mut current = image;
+    let mut part1 = 0;
+    for i in 0..50 {
+        current = enhance(&lookup, &current, lookup[0] && i % 2 == 1);
+        if i == 1 {
+            part1 = current.count_ones();
         }
     }
-    println!("{}", ch.fmt_compact());
+    let part2 = current.count_ones();
+
+    (part1, part2)
 }
 
 pub fn day20(test_mode: bool, print: bool) {
@@ -120,61 +65,17 @@ pub fn day20(test_mode: bool, print: bool) {
     let input_str = if test_mode {
         TEST_EXAMPLE
     } else {
-        &file_str.trim_end()
+        &file_str
     };
 
-    let (lookup, grid) = {
-        let mut first_part = true;
-        let mut lookup = Vec::new();
-        let mut grid_data = Vec::<u8>::with_capacity(100*100);
-        let mut grid_cols = 0;
-        for line in input_str.lines() {
-            if first_part {
-                if line.is_empty() {
-                    first_part = false;
-                } else {
-                    lookup = line.chars()
-                        .map(|c| match c { '#' => 1, '.' => 0, _ => unimplemented!()})
-                        .collect::<Vec<u8>>();
-                }
-            } else {
-                grid_data.extend(line.chars()
-                    .map(|c| match c { '#' => 1, '.' => 0, _ => unimplemented!()}));
-                grid_cols += 1;
-            }
-        }
-        (lookup, Grid{rows: grid_data.len() / grid_cols, cols: grid_cols, data: grid_data})
-    };
-
-    // Handles flipping patterns.
-    let alt_outsides = if lookup[0] == 1 { 1 } else { 0 };
-
-    let after1 = enhance(&lookup, &grid, 0);
-    let after2 = enhance(&lookup, &after1, alt_outsides);
-    let mut sum = 0usize;
-    for x in after2.data {
-        sum += x as usize;
-    }
-
-    let part1 = sum;
+    let (part1, part2) = solve(input_str);
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 35 } else { 5379 });
-
-    let mut current = grid;
-    for i in 0..50 {
-        current = enhance(&lookup, &current, alt_outsides * (i % 2));
-    }
-    let mut sum = 0usize;
-    for x in current.data {
-        sum += x as usize;
-    }
-
-    let part2 = sum;
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 3351 } else { 17917 });
 }
 
-const TEST_EXAMPLE: &'static str = "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
+const TEST_EXAMPLE: &str = "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
 
 #..#.
 #....
@@ -1,8 +1,16 @@
 use std::cmp::{min, max};
 
-use rustc_hash::FxHashMap;
+use crate::utils::*;
 
-type AnswerCache = FxHashMap<(i32, i32, i32, i32), (usize, usize)>;
+// (at1, score1, at2, score2), for the player about to move and then the other one.
+type GameState = (i32, i32, i32, i32);
+
+// Positions are 1..=10, and only scores below 21 get cached.
+fn state_index(&(at1, score1, at2, score2): &GameState) -> usize {
+    ((((at1 - 1) * 21 + score1) * 10 + at2 - 1) * 21 + score2) as usize
+}
+
+type AnswerCache = Memo<GameState, (usize, usize), DenseCache<GameState, (usize, usize), fn(&GameState) -> usize>>;
 
 struct DeterministicDie {
     value: i32,
@@ -35,32 +43,23 @@ fn sim(cache: &mut AnswerCache, at1: i32, score1: i32, at2: i32, score2: i32) ->
     if score1 >= 21 { return (1, 0); }
     if score2 >= 21 { return (0, 1); }
 
-    if let Some(result) = cache.get(&(at1, score1, at2, score2)) {
-        return *result;
-    }
-
-    let mut wins1 = 0;
-    let mut wins2 = 0;
+    cache.get_or_compute((at1, score1, at2, score2), |cache| {
+        let mut wins1 = 0;
+        let mut wins2 = 0;
 
-    for (mv, times) in ROLLS3 {
-        let at_next = (at1 + mv - 1) % 10 + 1;
-        let (w2, w1) = sim(cache, at2, score2, at_next, score1 + at_next);
-        wins1 += w1 * times;
-        wins2 += w2 * times;
-    }
+        for (mv, times) in ROLLS3 {
+            let at_next = (at1 + mv - 1) % 10 + 1;
+            let (w2, w1) = sim(cache, at2, score2, at_next, score1 + at_next);
+            wins1 += w1 * times;
+            wins2 += w2 * times;
+        }
 
-    cache.insert((at1, score1, at2, score2), (wins1, wins2));
-    (wins1, wins2)
+        (wins1, wins2)
+    })
 }
 
-pub fn day21(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input21.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        TEST_EXAMPLE
-    } else {
-        &file_str.trim_end()
-    };
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let input_str = input_str.trim_end();
 
     let mut lines = input_str.lines();
     let p1_start = lines.next().unwrap().split(" ").last().unwrap().parse::<i32>().unwrap();
@@ -89,20 +88,32 @@ pub fn day21(test_mode: bool, print: bool) {
 
     let loss = min(score1, score2);
 
-
     let part1 = loss as usize * die.rolls;
-    if print { println!("Part 1: {}", part1); }
-    assert_eq!(part1, if test_mode { 739785 } else { 920079 });
 
-    let mut cache = AnswerCache::with_capacity_and_hasher(10000, Default::default());
+    let mut cache = AnswerCache::with_cache(DenseCache::new(10 * 21 * 10 * 21, state_index));
     let (wins1, wins2) = sim(&mut cache, p1_start, 0, p2_start, 0);
     // println!("WINS: {}, {}", wins1, wins2);
 
-
     let part2 = max(wins1, wins2);
+
+    (part1, part2)
+}
+
+pub fn day21(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input21.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        TEST_EXAMPLE
+    } else {
+        &file_str
+    };
+
+    let (part1, part2) = solve(input_str);
+    if print { println!("Part 1: {}", part1); }
+    assert_eq!(part1, if test_mode { 739785 } else { 920079 });
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 444356092776315 } else { 56852759190649 });
 }
 
-const TEST_EXAMPLE: &'static str = "Player 1 starting position: 4
+const TEST_EXAMPLE: &str = "Player 1 starting position: 4
 Player 2 starting position: 8";
\ No newline at end of file
@@ -1,184 +1,77 @@
-use crate::utils::*;
-
-use std::ops::{Index, IndexMut};
-use std::cmp::{min, max};
-
-use regex::Regex;
-
-#[derive(Clone)]
-pub struct Grid3<T> {
-    pub dim1: usize,
-    pub dim2: usize,
-    pub dim3: usize,
-    pub data: Vec<T>,
-}
-
-impl<T> Grid3<T> {
-    pub fn filled(dim1: usize, dim2: usize, dim3: usize, value: T) -> Self
-        where T: Clone
-    {
-        Self{dim1, dim2, dim3, data: vec![value; dim1 * dim2 * dim3]}
-    }
+use std::io;
 
-    #[inline]
-    pub fn loc(&self, x: usize, y: usize, z: usize) -> usize{
-        x * (self.dim2 * self.dim3) + y * self.dim3 + z
-    }
-}
-
-impl<T> Index<(usize, usize, usize)> for Grid3<T> {
-    type Output = T;
-
-    fn index(&self, pt: (usize, usize, usize)) -> &Self::Output {
-        &self.data[self.loc(pt.0, pt.1, pt.2)]
-    }
-}
-
-impl<T> IndexMut<(usize, usize, usize)> for Grid3<T> {
-    fn index_mut(&mut self, pt: (usize, usize, usize)) -> &mut Self::Output {
-        let idx = self.loc(pt.0, pt.1, pt.2);
-        &mut self.data[idx]
-    }
-}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct Prism {
-    a: Pt3,
-    b: Pt3,
-}
+use crate::utils::*;
 
-impl Prism {
-    // Inclusive of endpoints
-    fn volume(&self) -> usize {
-        (self.b.x - self.a.x + 1) as usize *
-        (self.b.y - self.a.y + 1) as usize *
-        (self.b.z - self.a.z + 1) as usize
-    }
+// "on x=-20..26,y=-36..17,z=-47..7"
+fn parse_step(scan: &mut ByteScanner) -> Option<(bool, Cuboid<3>)> {
+    let on = scan.word()? == "on";
+    let mut range = |prefix: &str| {
+        scan.tag(prefix)?;
+        let first = scan.int()?;
+        scan.tag("..")?;
+        Some(Interval::inclusive(first, scan.int()?))
+    };
+    let ranges = [range(" x=")?, range(",y=")?, range(",z=")?];
+    Some((on, Cuboid::new(ranges)))
 }
 
-// pub fn parse_steps(input_str: &str) -> Vec::<(bool, i32, i32, i32, i32, i32, i32)> {
-fn parse_steps(input_str: &str) -> Vec::<(bool, Prism)> {
-    let re_parse_input = Regex::new(r"(o[nf]*) x=([-]?\d+)\.\.([-]?\d+),y=([-]?\d+)\.\.([-]?\d+),z=([-]?\d+)\.\.([-]?\d+)").unwrap();
+fn parse_steps(input_str: &str) -> Vec::<(bool, Cuboid<3>)> {
+    let mut scan = ByteScanner::new(input_str);
     let mut steps = Vec::with_capacity(32);
-    for line in input_str.lines() {
-        let captures = re_parse_input.captures(line).unwrap();
-        steps.push((
-            &captures[1] == "on",
-            Prism{a: Pt3::new(
-                    captures[2].parse::<i32>().unwrap(),
-                    captures[4].parse::<i32>().unwrap(),
-                    captures[6].parse::<i32>().unwrap()),
-                  b: Pt3::new(
-                    captures[3].parse::<i32>().unwrap(),
-                    captures[5].parse::<i32>().unwrap(),
-                    captures[7].parse::<i32>().unwrap())}
-        ));
+    scan.skip_whitespace();
+    while !scan.at_end() {
+        let at = scan.clone();
+        steps.push(parse_step(&mut scan).unwrap_or_else(|| panic!("Bad step: {:?}", at.clone().line().unwrap_or(""))));
+        scan.skip_whitespace();
     }
     steps
 }
 
-fn count_on_helper(steps: &Vec<(bool, Prism)>, axis: usize, scratch: &mut Vec<i32>) -> usize {
-    if steps.len() == 0 {
-        return 0;
-    }
-    if steps.len() == 1 {
-        return if steps[0].0 {
-                steps[0].1.volume()
-            } else { 0 };
-    }
-
-    // Do they overlap fully?
-    let mut overlap_fully = true;
-    for i in 1..steps.len() {
-        if steps[i].1 != steps[0].1 {
-            overlap_fully = false;
-            break;
-        }
-    }
-    if overlap_fully {
-        let step = steps.last().unwrap();
-        if step.0 == false {
-            return 0;
-        }
-        return step.1.volume();
-    }
-
-    // let mut splits = Vec::<i32>::with_capacity(steps.len() * 2);
-    let splits = scratch;
-    splits.clear();
-    for step in steps {
-        let prism = &step.1;
-        let a = match axis { 0 => prism.a.x, 1 => prism.a.y, 2 => prism.a.z, _ => unimplemented!() };
-        let b = match axis { 0 => prism.b.x, 1 => prism.b.y, 2 => prism.b.z, _ => unimplemented!() };
-        splits.push(a);
-        splits.push(b + 1);
+fn reboot(input_str: &str) -> CuboidSet<3> {
+    let mut reactor = CuboidSet::new();
+    for (on, region) in parse_steps(input_str.trim_end()) {
+        reactor.set(region, on);
     }
-    splits.sort_unstable();
-    splits.dedup();
-    let middle = splits[splits.len() / 2];
+    reactor
+}
 
-    let mut lower = Vec::<(bool, Prism)>::with_capacity(steps.len());
-    let mut upper = Vec::<(bool, Prism)>::with_capacity(steps.len());
-    for step in steps {
-        match axis {
-            0 => {
-                if step.1.a.x >= middle {
-                    upper.push(step.clone());
-                } else if step.1.b.x < middle {
-                    lower.push(step.clone());
-                } else {
-                    // Split
-                    let mut lower_step = step.clone();
-                    lower_step.1.b.x = middle - 1;
-                    lower.push(lower_step);
-                    let mut upper_step = step.clone();
-                    upper_step.1.a.x = middle;
-                    upper.push(upper_step);
-                }
-            }
-            1 => {
-                if step.1.a.y >= middle {
-                    upper.push(step.clone());
-                } else if step.1.b.y < middle {
-                    lower.push(step.clone());
-                } else {
-                    // Split
-                    let mut lower_step = step.clone();
-                    lower_step.1.b.y = middle - 1;
-                    lower.push(lower_step);
-                    let mut upper_step = step.clone();
-                    upper_step.1.a.y = middle;
-                    upper.push(upper_step);
+const INIT_REGION: i32 = 50;
+
+// The initialization region after the reboot as voxels, indexed (z, y, x) from -50.
+fn init_voxels(reactor: &CuboidSet<3>) -> Grid3<u8> {
+    let side = 2 * INIT_REGION as usize + 1;
+    let region = Cuboid::inclusive([-INIT_REGION; 3], [INIT_REGION; 3]);
+    let mut voxels = Grid3::filled(side, side, side, 0u8);
+    let at = |v: i32| (v + INIT_REGION) as usize;
+    for lit in reactor.boxes() {
+        let [xs, ys, zs] = lit.intersect(&region).ranges;
+        for z in zs {
+            for y in ys {
+                for x in xs {
+                    voxels[(at(z), at(y), at(x))] = 255;
                 }
             }
-            2 => {
-                if step.1.a.z >= middle {
-                    upper.push(step.clone());
-                } else if step.1.b.z < middle {
-                    lower.push(step.clone());
-                } else {
-                    // Split
-                    let mut lower_step = step.clone();
-                    lower_step.1.b.z = middle - 1;
-                    lower.push(lower_step);
-                    let mut upper_step = step.clone();
-                    upper_step.1.a.z = middle;
-                    upper.push(upper_step);
-                }
-            }
-            _ => unimplemented!()
         }
     }
+    voxels
+}
 
-    // println!("Split on {} at {}  splits {} into {}, {}", axis, middle, steps.len(), lower.len(), upper.len());
-
-    count_on_helper(&lower, (axis + 1) % 3, splits) +
-    count_on_helper(&upper, (axis + 1) % 3, splits)
+// The initialization region one z layer at a time, lit cubes in white.
+pub fn export(input_str: &str, frames: &mut FrameWriter) -> io::Result<()> {
+    let voxels = init_voxels(&reboot(input_str));
+    for z in 0..voxels.dim1 {
+        frames.pgm(&voxels.slice(0, z))?;
+    }
+    Ok(())
 }
 
-fn count_on(steps: &Vec<(bool, Prism)>) -> usize {
-    let mut scratch = Vec::<i32>::with_capacity(steps.len() * 2);
-    count_on_helper(steps, 0, &mut scratch)
+pub fn solve(input_str: &str) -> (usize, usize) {
+    // Applies every step, then measures the initialization region for part 1.
+    let reactor = reboot(input_str);
+    let part1 = reactor.volume_within(&Cuboid::inclusive([-INIT_REGION; 3], [INIT_REGION; 3]));
+    let part2 = reactor.volume();
+
+    (part1, part2)
 }
 
 pub fn day22(test_mode: bool, print: bool) {
@@ -187,55 +80,22 @@ pub fn day22(test_mode: bool, print: bool) {
     let input_str = if test_mode {
         TEST_EXAMPLE
     } else {
-        &file_str.trim_end()
+        &file_str
     };
 
-
-
-    let mut steps = parse_steps(input_str);
-
-    let mut core = Grid3::<bool>::filled(101, 101, 101, false);
-
-    for step in &steps {
-        let x_min = max(-50, step.1.a.x);
-        let x_max = min(50, step.1.b.x);
-        let y_min = max(-50, step.1.a.y);
-        let y_max = min(50, step.1.b.y);
-        let z_min = max(-50, step.1.a.z);
-        let z_max = min(50, step.1.b.z);
-        // println!("step: {:?}  --> {}..{} {}..{} {}..{}", step, x_min, x_max, y_min, y_max, z_min, z_max);
-        if x_min > 50 || x_max < -50 || y_min > 50 || y_max < -50 || z_min > 50 || z_max < -50 {
-            // println!("NOPE");
-            continue;
-        }
-        for x in ((x_min + 50) as usize)..=((x_max + 50) as usize) {
-            for y in ((y_min + 50) as usize)..=((y_max + 50) as usize) {
-                for z in ((z_min + 50) as usize)..=((z_max + 50) as usize) {
-                    core[(x, y, z)] = step.0;
-                }
-            }
-        }
-    }
-
-    let mut sum = 0;
-    for d in core.data {
-        if d { sum += 1; }
-    }
-
-    let part1 = sum;
+    let (part1, mut part2) = solve(input_str);
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 590784 } else { 602574 });
 
     if test_mode {
-        steps = parse_steps(REBOOT_EXAMPLE);
+        part2 = solve(REBOOT_EXAMPLE).1;
     }
 
-    let part2 = count_on(&steps);
     if print { println!("Part 2: {}", part2); }
     assert_eq!(part2, if test_mode { 2758514936282235 } else { 1288707160324706 });
 }
 
-const TEST_EXAMPLE: &'static str = "on x=-20..26,y=-36..17,z=-47..7
+const TEST_EXAMPLE: &str = "on x=-20..26,y=-36..17,z=-47..7
 on x=-20..33,y=-21..23,z=-26..28
 on x=-22..28,y=-29..23,z=-38..16
 on x=-46..7,y=-6..46,z=-50..-1
@@ -258,7 +118,7 @@ on x=-41..9,y=-7..43,z=-33..15
 on x=-54112..-39298,y=-85059..-49293,z=-27449..7877
 on x=967..23432,y=45373..81175,z=27513..53682";
 
-const REBOOT_EXAMPLE: &'static str = "on x=-5..47,y=-31..22,z=-19..33
+const REBOOT_EXAMPLE: &str = "on x=-5..47,y=-31..22,z=-19..33
 on x=-44..5,y=-27..21,z=-14..35
 on x=-49..-1,y=-11..42,z=-10..38
 on x=-20..34,y=-40..6,z=-44..1
@@ -1,15 +1,12 @@
-use rustc_hash::FxHashSet;
-
+use crate::repl;
 use crate::utils::*;
 
-use std::collections::BinaryHeap;
-
 const R_HALL: usize = 1;
 
 #[inline]
 fn can_stop(r: usize) -> bool {
-    r % 2 == 0 ||
-    r < 3 || r > 9
+    r.is_multiple_of(2) ||
+    !(3..=9).contains(&r)
 }
 
 fn room_space(map: &Grid<u8>, room: u8) -> Option<usize> {
@@ -111,134 +108,154 @@ fn cost_to_go(locs: &[(usize, usize)]) -> usize {
     cost
 }
 
-fn solve_search(init_map: &Grid<u8>, init_locs: &[(usize, usize)], cnt: &mut usize) -> usize {
-    // Checks for being finished
-    if is_solved(init_map) {
-        return 0;
-    }
+type Locs = Vec<(usize, usize)>;
+
+// Best-first search over amphipod positions, one popped state at a time. Keeps the moves
+// that led to each state only if H is Paths.
+struct Search<H: History<Locs>> {
+    empty_map: Grid<u8>,
+    scratch_map: Grid<u8>,
+    astar: AStar<Locs, HashVisited<Locs>, H>,
+    // Reused between pops.
+    moves: Vec<(Locs, usize)>,
+}
 
-    let empty_map = {
-        let mut empty_map = init_map.clone();
-        for r in 0..empty_map.rows {
-            for c in 0..empty_map.cols {
-                if b'A' <= empty_map[(r, c)] && empty_map[(r, c)] <= b'D' {
-                    empty_map[(r, c)] = 0;
+impl Search<()> {
+    fn new(init_map: &Grid<u8>, init_locs: &[(usize, usize)]) -> Self {
+        let empty_map = {
+            let mut empty_map = init_map.clone();
+            for r in 0..empty_map.rows {
+                for c in 0..empty_map.cols {
+                    if b'A' <= empty_map[(r, c)] && empty_map[(r, c)] <= b'D' {
+                        empty_map[(r, c)] = 0;
+                    }
                 }
             }
-        }
-        empty_map
-    };
-    let mut scratch_map = empty_map.clone();
+            empty_map
+        };
+        let scratch_map = empty_map.clone();
 
-    let mut seen = FxHashSet::<Vec<(usize, usize)>>::with_capacity_and_hasher(100, Default::default());
-    let mut pq = BinaryHeap::with_capacity(100);
-    pq.push(ByFirstRev((cost_to_go(init_locs), 0usize, init_locs.to_owned())));
+        Search{empty_map, scratch_map, astar: AStar::new(init_locs.to_owned()), moves: Vec::with_capacity(32)}
+    }
 
-    while !pq.is_empty() {
-        *cnt += 1;
-        let ByFirstRev((_, cost, locs)) = pq.pop().unwrap();
-        // println!("Visiting [{}] cost = {}: {:?}\n{}", *cnt, cost, locs, map.fmt_map());
+    fn with_paths(self) -> Search<Paths<Locs>> {
+        let Search{empty_map, scratch_map, astar, moves} = self;
+        Search{empty_map, scratch_map, astar: astar.with_paths(), moves}
+    }
+}
 
-        if seen.contains(&locs) {
-            continue;
-        }
-        seen.insert(locs.clone());
-
-        // Renders the map for collision checking
-        let map = {
-            scratch_map.clone_from(&empty_map);
-            for (i, loc) in locs.iter().enumerate() {
-                let ch = b'A' + (i * 4 / locs.len()) as u8;
-                scratch_map[*loc] = ch;
-            }
-            &scratch_map
-        };
+impl<H: History<Locs>> Search<H> {
+    // Renders the map for collision checking
+    fn render(&mut self, locs: &[(usize, usize)]) -> &Grid<u8> {
+        render_into(&mut self.scratch_map, &self.empty_map, locs);
+        &self.scratch_map
+    }
+
+    // Pops the next unseen state and queues up its moves.
+    //
+    // Returns (locs, cost, handle, is_solved), or None once the queue runs dry.
+    fn pop(&mut self) -> Option<(Locs, usize, H::Handle, bool)> {
+        let (locs, cost, handle) = self.astar.pop()?;
+        // println!("Visiting [{}] cost = {}: {:?}\n{}", self.astar.stats.popped, cost, locs, map.fmt_map());
 
-        if is_solved(&map) {
-            return cost;
+        render_into(&mut self.scratch_map, &self.empty_map, &locs);
+        if is_solved(&self.scratch_map) {
+            return Some((locs, cost, handle, true));
         }
 
-        // First we do placements, since placements are always good.
-        let mut placement_successful = false;
-        for i in 0..locs.len() {
-            let loc = locs[i];
-            let ch = map[loc];
-            let per_step_cost = 10usize.pow((ch - b'A').into());
-
-            if loc.0 == R_HALL {
-                // Hallway to room
-                let goal_room = ch - b'A';
-                let goal_col = (goal_room * 2 + 3) as usize;
-                if let Some(space) = room_space(&map, goal_room) {
-                    // println!("Space for {} at ({}, {})", ch as char, space, goal_col);
-                    let mut reachable = true;
-
-                    let mut c = goal_col;
-                    let dir = if loc.1 < goal_col { -1 } else { 1 as i32 };
-                    while c != loc.1 {
-                        if map[(R_HALL, c)] != 0 {
-                            reachable = false;
-                            break;
-                        }
-                        c = (c as i32 + dir) as usize;
-                    }
-                    if reachable {
-                        // println!("Placed {} -> room {}", ch as char, space);
-                        let hall_steps = if loc.1 > goal_col { loc.1 - goal_col } else { goal_col - loc.1 };
-                        let move_cost = (space - R_HALL + hall_steps) * per_step_cost;
+        find_moves(&self.scratch_map, &locs, &mut self.moves);
+        for (moved, move_cost) in self.moves.drain(..) {
+            let to_go = cost_to_go(&moved);
+            self.astar.push(handle, moved, cost + move_cost, to_go);
+        }
+        Some((locs, cost, handle, false))
+    }
+}
 
-                        let moved = with_move(&locs, i, (space, goal_col));
-                        let to_go = cost_to_go(&moved);
-                        pq.push(ByFirstRev((cost + move_cost + to_go, cost + move_cost, moved)));
+fn render_into(map: &mut Grid<u8>, empty_map: &Grid<u8>, locs: &[(usize, usize)]) {
+    map.clone_from(empty_map);
+    for (i, loc) in locs.iter().enumerate() {
+        let ch = b'A' + (i * 4 / locs.len()) as u8;
+        map[*loc] = ch;
+    }
+}
 
-                        placement_successful = true;
+// Fills in (next locs, move cost) for every move worth trying from `locs`.
+fn find_moves(map: &Grid<u8>, locs: &[(usize, usize)], moves: &mut Vec<(Locs, usize)>) {
+    // First we do placements, since placements are always good.
+    for i in 0..locs.len() {
+        let loc = locs[i];
+        let ch = map[loc];
+        let per_step_cost = 10usize.pow((ch - b'A').into());
+
+        if loc.0 == R_HALL {
+            // Hallway to room
+            let goal_room = ch - b'A';
+            let goal_col = (goal_room * 2 + 3) as usize;
+            if let Some(space) = room_space(map, goal_room) {
+                // println!("Space for {} at ({}, {})", ch as char, space, goal_col);
+                let mut reachable = true;
+
+                let mut c = goal_col;
+                let dir = if loc.1 < goal_col { -1 } else { 1_i32 };
+                while c != loc.1 {
+                    if map[(R_HALL, c)] != 0 {
+                        reachable = false;
                         break;
                     }
+                    c = (c as i32 + dir) as usize;
                 }
-            }
-        }
+                if reachable {
+                    // println!("Placed {} -> room {}", ch as char, space);
+                    let hall_steps = loc.1.abs_diff(goal_col);
+                    let move_cost = (space - R_HALL + hall_steps) * per_step_cost;
 
-        if placement_successful {
-            continue;
+                    moves.push((with_move(locs, i, (space, goal_col)), move_cost));
+                    return;
+                }
+            }
         }
+    }
 
-        // Then we do moves to the hallway.
-        for i in 0..locs.len() {
-            let loc = locs[i];
-            let ch = map[loc];
-            let per_step_cost = 10usize.pow((ch - b'A').into());
-
-            if loc.0 != R_HALL {
-                // Room to hallway
-                let room = ((loc.1 - 3) / 2) as u8;
-                // println!("Room leavable? {} {:?}", room, loc);
-                let steps_out = room_leavable(&map, room, loc.0);
-                if steps_out > 0 {
-                    // println!("Move {} from room {}", ch as char, room);
-
-                    // Possible hallway spots to move to
-                    for c in hall_spots(&map, loc.1) {
-                        let move_cost = (steps_out + (c as i32 - loc.1 as i32).abs() as usize) * per_step_cost;
-                        let moved = with_move(&locs, i, (R_HALL, c));
-                        let to_go = cost_to_go(&moved);
-                        pq.push(ByFirstRev((cost + move_cost + to_go, cost + move_cost, moved)));
-                    }
+    // Then we do moves to the hallway.
+    for i in 0..locs.len() {
+        let loc = locs[i];
+        let ch = map[loc];
+        let per_step_cost = 10usize.pow((ch - b'A').into());
+
+        if loc.0 != R_HALL {
+            // Room to hallway
+            let room = ((loc.1 - 3) / 2) as u8;
+            // println!("Room leavable? {} {:?}", room, loc);
+            let steps_out = room_leavable(map, room, loc.0);
+            if steps_out > 0 {
+                // println!("Move {} from room {}", ch as char, room);
+
+                // Possible hallway spots to move to
+                for c in hall_spots(map, loc.1) {
+                    let move_cost = (steps_out + (c as i32 - loc.1 as i32).unsigned_abs() as usize) * per_step_cost;
+                    moves.push((with_move(locs, i, (R_HALL, c)), move_cost));
                 }
             }
         }
+    }
+}
 
+fn solve_search<H: History<Locs>>(mut search: Search<H>) -> Route<Locs> {
+    while let Some((_, cost, handle, solved)) = search.pop() {
+        if solved {
+            return Route{cost, path: search.astar.path_to(handle), stats: search.astar.stats};
+        }
     }
     unimplemented!();
 }
 
 fn parse_input(input_str: &str) -> (Grid<u8>, Vec<(usize, usize)>) {
-    let mut grid = Grid::<u8>::filled(7, 13, 0);
+    let grid = Grid::parse_padded(input_str, b' ', |ch| if ch == b'.' { 0 } else { ch });
     let mut locs = Vec::with_capacity(16);
-    for (r, line) in input_str.lines().enumerate() {
-        for (c, ch) in line.chars().enumerate() {
-            let ch = ch as u8;
-            grid[(r, c)] = if ch == b'.' { 0 } else { ch };
-            if b'A' <= ch && ch <= b'D' {
+    for r in 0..grid.rows {
+        for c in 0..grid.cols {
+            if (b'A'..=b'D').contains(&grid[(r, c)]) {
                 locs.push((r, c));
             }
         }
@@ -250,38 +267,11 @@ fn parse_input(input_str: &str) -> (Grid<u8>, Vec<(usize, usize)>) {
     (grid, locs)
 }
 
-pub fn day23(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input23.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        TEST_EXAMPLE
-    } else {
-        &file_str.trim_end()
-    };
+// The starting map and amphipod locations for both parts.
+fn parse_parts(input_str: &str) -> ((Grid<u8>, Locs), (Grid<u8>, Locs)) {
+    let input_str = input_str.trim_end();
 
-    let mut start_grid = Grid::<u8>::filled(5, 13, 0);
-    let mut start_locs = [(0usize, 0usize); 8];
-    for (r, line) in input_str.lines().enumerate() {
-        for (c, ch) in line.chars().enumerate() {
-            let ch = ch as u8;
-            start_grid[(r, c)] = if ch == b'.' { 0 } else { ch };
-            if b'A' <= ch && ch <= b'D' {
-                let p = (ch - b'A') * 2;
-                if start_locs[p as usize] == (0, 0) {
-                    start_locs[p as usize] = (r, c);
-                } else {
-                    start_locs[(p + 1) as usize] = (r, c);
-                }
-            }
-        }
-    }
-
-    let mut cnt1 = 0;
-    let part1 = solve_search(&start_grid, &start_locs, &mut cnt1);
-    if print { println!("Part 1: {} (visited {})", part1, cnt1); }
-    assert_eq!(part1, if test_mode { 12521 } else { 14371 });
-
-    let mut ext_input = String::with_capacity(input_str.len() + (start_grid.cols + 1) * 2);
+    let mut ext_input = String::with_capacity(input_str.len() + EXTENSION.len());
     for (i, line) in input_str.lines().enumerate() {
         ext_input.push_str(line);
         ext_input.push('\n');
@@ -290,20 +280,136 @@ pub fn day23(test_mode: bool, print: bool) {
         }
     }
 
-    let (ext_grid, ext_locs) = parse_input(&ext_input);
+    (parse_input(input_str), parse_input(&ext_input))
+}
+
+// The cheapest way to sort each part's burrow, along with the moves.
+fn solve_routes(input_str: &str) -> (Route<Locs>, Route<Locs>) {
+    let ((start_grid, start_locs), (ext_grid, ext_locs)) = parse_parts(input_str);
+    (solve_search(Search::new(&start_grid, &start_locs).with_paths()),
+     solve_search(Search::new(&ext_grid, &ext_locs).with_paths()))
+}
+
+pub fn solve(input_str: &str) -> (usize, usize) {
+    let ((start_grid, start_locs), (ext_grid, ext_locs)) = parse_parts(input_str);
+    (solve_search(Search::new(&start_grid, &start_locs)).cost, solve_search(Search::new(&ext_grid, &ext_locs)).cost)
+}
+
+pub struct SearchSession {
+    parts: [(Grid<u8>, Locs); 2],
+    part: usize,
+    search: Search<Paths<Locs>>,
+    // The last popped state, and its handle for the route to it.
+    last_popped: Option<(Locs, usize)>,
+}
+
+impl SearchSession {
+    pub fn new(input_str: &str) -> Self {
+        let (part1, part2) = parse_parts(input_str);
+        let search = Search::new(&part1.0, &part1.1).with_paths();
+        SearchSession{parts: [part1, part2], part: 1, search, last_popped: None}
+    }
+
+    fn fmt_state(&mut self, cost: usize, locs: &[(usize, usize)]) -> String {
+        let to_go = cost_to_go(locs);
+        let (queued, seen) = (self.search.astar.queued(), self.search.astar.stats.expanded);
+        format!("cost {}, estimated to go {}  ({} queued, {} seen)\n{}",
+            cost, to_go, queued, seen, self.search.render(locs).fmt_map())
+    }
+}
+
+impl repl::Session for SearchSession {
+    fn help(&self) -> &'static str {
+        "\
+part 1|2            restart the search on that part's burrow
+pop [N]             expand N states (default 1) and show the last one
+peek                show the next state in the queue without expanding it
+route               show the moves that led to the last popped state"
+    }
+
+    fn run(&mut self, cmd: &str, args: &[&str]) -> Result<String, String> {
+        match cmd {
+            "part" => {
+                let part: usize = repl::arg(args, 0, None)?;
+                if part != 1 && part != 2 {
+                    return Err(format!("No part {}", part));
+                }
+                let (map, locs) = &self.parts[part - 1];
+                self.part = part;
+                self.search = Search::new(map, locs).with_paths();
+                self.last_popped = None;
+                Ok(format!("Searching part {}\n{}", part, map.fmt_map()))
+            }
+            "pop" => {
+                let n: usize = repl::arg(args, 0, Some(1))?;
+                let mut last = None;
+                for _ in 0..n {
+                    match self.search.pop() {
+                        Some((locs, cost, handle, solved)) => {
+                            last = Some((locs, cost, handle, solved));
+                            if solved { break; }
+                        }
+                        None => break,
+                    }
+                }
+                match last {
+                    Some((locs, cost, handle, solved)) => {
+                        let state = self.fmt_state(cost, &locs);
+                        self.last_popped = Some((locs, handle));
+                        let solved = if solved { "SOLVED with " } else { "" };
+                        Ok(format!("Part {}, visit {}: {}{}", self.part, self.search.astar.stats.popped, solved, state))
+                    }
+                    None => Err("The queue is empty".to_string()),
+                }
+            }
+            "peek" => {
+                let (locs, cost) = match self.search.astar.peek() {
+                    Some((locs, cost)) => (locs.clone(), cost),
+                    None => return Err("The queue is empty".to_string()),
+                };
+                Ok(self.fmt_state(cost, &locs))
+            }
+            "route" => {
+                let handle = self.last_popped.as_ref().ok_or("Nothing popped yet")?.1;
+                let mut out = String::new();
+                for (step, locs) in self.search.astar.path_to(handle).iter().enumerate() {
+                    out.push_str(&format!("Step {}:\n{}", step, self.search.render(locs).fmt_map()));
+                }
+                Ok(out)
+            }
+            _ => Err(repl::unknown(cmd)),
+        }
+    }
+}
+
+pub fn day23(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input23.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        TEST_EXAMPLE
+    } else {
+        &file_str
+    };
 
-    let mut cnt2 = 0;
-    let part2 = solve_search(&ext_grid, &ext_locs, &mut cnt2);
-    if print { println!("Part 2: {} (visited {})", part2, cnt2); }
+    // Keeping the moves slows the search down, so only when they're shown.
+    let (part1, part2) = if print {
+        let (route1, route2) = solve_routes(input_str);
+        println!("Part 1: {} ({} moves, visited {})", route1.cost, route1.path.len() - 1, route1.stats.popped);
+        println!("Part 2: {} ({} moves, visited {})", route2.cost, route2.path.len() - 1, route2.stats.popped);
+        (route1.cost, route2.cost)
+    } else {
+        solve(input_str)
+    };
+    assert_eq!(part1, if test_mode { 12521 } else { 14371 });
     assert_eq!(part2, if test_mode { 44169 } else { 40941 });
 }
 
-const TEST_EXAMPLE: &'static str = "#############
+const TEST_EXAMPLE: &str = "#############
 #...........#
 ###B#C#B#D###
   #A#D#C#A#
   #########";
 
-const EXTENSION: &'static str ="  #D#C#B#A#
+const EXTENSION: &str ="  #D#C#B#A#
   #D#B#A#C#
 ";
\ No newline at end of file
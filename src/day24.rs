@@ -1,5 +1,7 @@
 use regex::Regex;
 
+use crate::repl;
+
 #[derive(Debug)]
 enum Var {
     Imm(i64),
@@ -24,7 +26,7 @@ fn parse_reg(string: &str) -> usize {
 #[inline]
 fn parse_var(string: &str) -> Var {
     let b = string.bytes().next().unwrap();
-    if b'w' <= b && b <= b'z' {
+    if (b'w'..=b'z').contains(&b) {
         Var::Reg((b - b'w').into())
     } else {
         Var::Imm(string.parse().unwrap())
@@ -53,7 +55,6 @@ fn eval_var(v: &Var, regs: &[i64]) -> i64 {
     }
 }
 
-#[allow(dead_code)]
 fn letters(x: i64) -> String {
     let mut s = Vec::<u8>::with_capacity(15);
     let sgn = x.signum();
@@ -84,11 +85,11 @@ fn run(program: &Vec<Op>, input: &[u8], mut trace_maybe: Option<&mut Vec<i64>>)
                 first = false;
                 prog_input.next().unwrap()
             },
-            Op::Add(r, v) => regs[*r] += eval_var(&v, &regs),
-            Op::Mul(r, v) => regs[*r] *= eval_var(&v, &regs),
-            Op::Div(r, v) => regs[*r] /= eval_var(&v, &regs),
-            Op::Mod(r, v) => regs[*r] = regs[*r] % eval_var(&v, &regs),
-            Op::Eql(r, v) => regs[*r] = if regs[*r] == eval_var(&v, &regs) { 1 } else { 0 },
+            Op::Add(r, v) => regs[*r] += eval_var(v, &regs),
+            Op::Mul(r, v) => regs[*r] *= eval_var(v, &regs),
+            Op::Div(r, v) => regs[*r] /= eval_var(v, &regs),
+            Op::Mod(r, v) => regs[*r] %= eval_var(v, &regs),
+            Op::Eql(r, v) => regs[*r] = if regs[*r] == eval_var(v, &regs) { 1 } else { 0 },
         }
 
         // println!("  {:?}  -> {:?}", instr, regs);  
@@ -107,21 +108,20 @@ struct Block {
     added: i64,
 }
 
-pub fn day24(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input24.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        TEST_EXAMPLE
-    } else {
-        &file_str.trim_end()
-    };
-
+fn parse_program(input_str: &str) -> Vec<Op> {
     let mut program = Vec::<Op>::with_capacity(100);
     for line in input_str.lines() {
         let instr = parse_instr(line);
         // println!("INSTR {:?}", instr);
         program.push(instr);
     }
+    program
+}
+
+pub fn solve(input_str: &str) -> (String, String) {
+    let input_str = input_str.trim_end();
+
+    let program = parse_program(input_str);
 
     // Every block has the same structure:
     //
@@ -188,10 +188,9 @@ add z y").unwrap();
         }
     }
 
-    let z= run(&program, &value, None);
-    if print { println!("Part 1: ({})  {}", z, String::from_utf8_lossy(&value)); }
+    let z = run(&program, &value, None);
     assert_eq!(z, 0);
-    assert_eq!(value, b"39999698799429");
+    let part1 = String::from_utf8_lossy(&value).into_owned();
 
     // Minimizing
     let mut value = vec![0u8; 14];
@@ -205,13 +204,70 @@ add z y").unwrap();
         }
     }
 
-    let z= run(&program, &value, None);
-    if print { println!("Part 2: ({})  {}", z, String::from_utf8_lossy(&value)); }
+    let z = run(&program, &value, None);
     assert_eq!(z, 0);
-    assert_eq!(value, b"18116121134117");
+    let part2 = String::from_utf8_lossy(&value).into_owned();
+
+    (part1, part2)
+}
+
+pub struct AluSession {
+    program: Vec<Op>,
+}
+
+impl AluSession {
+    pub fn new(input_str: &str) -> Self {
+        AluSession{program: parse_program(input_str.trim_end())}
+    }
+}
+
+impl repl::Session for AluSession {
+    fn help(&self) -> &'static str {
+        "\
+run DIGITS          run the program on a 14-digit model number, showing z after each digit"
+    }
+
+    fn run(&mut self, cmd: &str, args: &[&str]) -> Result<String, String> {
+        match cmd {
+            "run" => {
+                let digits: String = repl::arg(args, 0, None)?;
+                if digits.len() != 14 || !digits.bytes().all(|b| (b'1'..=b'9').contains(&b)) {
+                    return Err(format!("Model numbers are 14 digits from 1-9, not {}", digits));
+                }
+
+                let mut trace = Vec::<i64>::with_capacity(14);
+                let z = run(&self.program, digits.as_bytes(), Some(&mut trace));
+                let mut out = String::new();
+                for (digit, z) in digits.chars().zip(&trace) {
+                    // z is a stack of base-26 "letters"
+                    out.push_str(&format!("{}  z = {:>12}  {}\n", digit, z, letters(*z)));
+                }
+                out.push_str(if z == 0 { "VALID" } else { "invalid" });
+                Ok(out)
+            }
+            _ => Err(repl::unknown(cmd)),
+        }
+    }
+}
+
+pub fn day24(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input24.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        TEST_EXAMPLE
+    } else {
+        &file_str
+    };
+
+    let (part1, part2) = solve(input_str);
+    if print { println!("Part 1: {}", part1); }
+    assert_eq!(part1, "39999698799429");
+
+    if print { println!("Part 2: {}", part2); }
+    assert_eq!(part2, "18116121134117");
 }
 
-const TEST_EXAMPLE: &'static str = "inp w
+const TEST_EXAMPLE: &str = "inp w
 add z w
 mod z 2
 div w 2
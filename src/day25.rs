@@ -1,95 +1,120 @@
+use std::io;
+
+use crate::animate::Animation;
 use crate::utils::*;
 
-const FREE: u8 = b'.';
-
-fn step(grid: &mut Grid<u8>) -> bool {
-    let mut any_moved = false;
-
-    // East-facing
-    for r in 0..grid.rows {
-        let wrap_move = grid[(r, 0)] == FREE && grid[(r, grid.cols - 1)] == b'>';
-
-        let mut moved = false;
-        for c in 0..(grid.cols - 1) {
-            if !moved && grid[(r, c)] == b'>' && grid[(r, c + 1)] == FREE {
-                grid[(r, c)] = FREE;
-                grid[(r, c + 1)] = b'>';
-                any_moved = true;
-                moved = true;
-            } else {
-                moved = false;
-            }
-        }
+// One bit per cell for each herd.
+struct Herds {
+    east: BitGrid,
+    south: BitGrid,
+}
 
-        if wrap_move {
-            let cols = grid.cols;
-            grid[(r, cols - 1)] = FREE;
-            grid[(r, 0)] = b'>';
-            any_moved = true;
+impl Herds {
+    fn to_grid(&self) -> Grid<u8> {
+        let mut grid = self.east.to_grid(b'>', b'.');
+        for (r, c) in self.south.iter_ones() {
+            grid[(r, c)] = b'v';
         }
+        grid
     }
+}
 
-    // South-facing
-    for c in 0..grid.cols {
-        let wrap_move = grid[(0, c)] == FREE && grid[(grid.rows - 1, c)] == b'v';
-
-        let mut moved = false;
-        for r in 0..(grid.rows - 1) {
-            if !moved && grid[(r, c)] == b'v' && grid[(r + 1, c)] == FREE {
-                grid[(r, c)] = FREE;
-                grid[(r + 1, c)] = b'v';
-                moved = true;
-                any_moved = true;
-            } else {
-                moved = false;
-            }
-        }
+// Moves a herd one cell along (wrapping) wherever the cell ahead is free.
+fn advance(herd: &mut BitGrid, occupied: &BitGrid, ahead: impl Fn(&BitGrid, isize) -> BitGrid) -> bool {
+    let mut arriving = ahead(herd, 1);
+    arriving &= &!occupied;
+    if !arriving.any() {
+        return false;
+    }
+    *herd ^= &ahead(&arriving, -1);
+    *herd |= &arriving;
+    true
+}
+
+fn step(herds: &mut Herds) -> bool {
+    let occupied = &herds.east | &herds.south;
+    let east_moved = advance(&mut herds.east, &occupied, BitGrid::shift_cols_wrapping);
+
+    let occupied = &herds.east | &herds.south;
+    let south_moved = advance(&mut herds.south, &occupied, BitGrid::shift_rows_wrapping);
+
+    east_moved || south_moved
+}
+
+fn parse_herds(input_str: &str) -> Herds {
+    let grid = Grid::parse(input_str, |ch| ch).unwrap();
+    Herds{east: BitGrid::from_grid(&grid, |ch| *ch == b'>'), south: BitGrid::from_grid(&grid, |ch| *ch == b'v')}
+}
+
+fn herd_palette(ch: char) -> Option<&'static str> {
+    match ch {
+        '>' => Some("92"),
+        'v' => Some("96"),
+        _ => Some("90"),
+    }
+}
 
-        if wrap_move {
-            let rows = grid.rows;
-            grid[(rows - 1, c)] = FREE;
-            grid[(0, c)] = b'v';
-            any_moved = true;
+pub fn animate(input_str: &str, anim: &mut Animation) {
+    let mut herds = parse_herds(input_str);
+    anim.frame("Initial state", &herds.to_grid(), herd_palette);
+    for i in 1..1000 {
+        let moved = step(&mut herds);
+        anim.frame(&format!("After {} steps", i), &herds.to_grid(), herd_palette);
+        if !moved {
+            break;
         }
     }
-    any_moved
 }
 
-pub fn day25(test_mode: bool, print: bool) {
-    const INPUT: &str = "inputs/input25.txt";
-    let file_str = std::fs::read_to_string(INPUT).unwrap();
-    let input_str = if test_mode {
-        TEST_EXAMPLE
-    } else {
-        &file_str.trim_end()
-    };
+fn herd_colors(grid: &Grid<u8>) -> Grid<Rgb> {
+    Grid{rows: grid.rows, cols: grid.cols, data: grid.data.iter().map(|ch| match ch {
+        b'>' => [60, 200, 90],
+        b'v' => [60, 140, 230],
+        _ => [0, 0, 0],
+    }).collect()}
+}
 
+// One frame per step, until the herds stop moving.
+pub fn export(input_str: &str, frames: &mut FrameWriter) -> io::Result<()> {
+    let mut herds = parse_herds(input_str);
+    frames.ppm(&herd_colors(&herds.to_grid()))?;
+    while step(&mut herds) {
+        frames.ppm(&herd_colors(&herds.to_grid()))?;
+    }
+    Ok(())
+}
 
-    let mut grid = {
-        let mut grid_data = Vec::<u8>::with_capacity(100*100);
-        let mut grid_rows = 0;
-        for line in input_str.lines() {
-            grid_data.extend(line.as_bytes());
-            grid_rows += 1;
-        }
-        Grid{rows: grid_rows, cols: grid_data.len() / grid_rows, data: grid_data}
-    };
+// There's no second part on the last day.
+pub fn solve(input_str: &str) -> usize {
+    let mut herds = parse_herds(input_str);
 
     let mut steps_until_fixed = 0;
     for i in 1..1000 {
-        if !step(&mut grid) {
+        if !step(&mut herds) {
             steps_until_fixed = i;
             break;
         }
-        // println!("{}:\n{}", i, grid.fmt_map());
+        // println!("{}:\n{}", i, herds.to_grid().fmt_map());
     }
 
-    let part1 = steps_until_fixed;
+    steps_until_fixed
+}
+
+pub fn day25(test_mode: bool, print: bool) {
+    const INPUT: &str = "inputs/input25.txt";
+    let file_str = std::fs::read_to_string(INPUT).unwrap();
+    let input_str = if test_mode {
+        TEST_EXAMPLE
+    } else {
+        &file_str
+    };
+
+    let part1 = solve(input_str);
     if print { println!("Part 1: {}", part1); }
     assert_eq!(part1, if test_mode { 58 } else { 417 });
 }
 
-const TEST_EXAMPLE: &'static str = "v...>>.vv>
+const TEST_EXAMPLE: &str = "v...>>.vv>
 .vv>>.vv..
 >>.>v>...v
 >>v>>.>.v.
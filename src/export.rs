@@ -0,0 +1,25 @@
+// Writes a day's intermediate states out as numbered PGM/PPM frames.
+
+use std::io;
+
+use crate::utils::FrameWriter;
+
+pub fn export(day: usize, dir: &str, scale: usize) {
+    let input_str = std::fs::read_to_string(format!("inputs/input{:02}.txt", day)).expect("Cannot open file");
+    let prefix = format!("day{:02}", day);
+    let mut frames = FrameWriter::new(dir, &prefix, scale).expect("Cannot create frame directory");
+    let exported: io::Result<()> = match day {
+        5 => crate::day05::export(&input_str, &mut frames),
+        9 => crate::day09::export(&input_str, &mut frames),
+        15 => crate::day15::export(&input_str, &mut frames),
+        20 => crate::day20::export(&input_str, &mut frames),
+        22 => crate::day22::export(&input_str, &mut frames),
+        25 => crate::day25::export(&input_str, &mut frames),
+        _ => {
+            println!("Day {} has nothing to export (try 5, 9, 15, 20, 22 or 25)", day);
+            return;
+        }
+    };
+    exported.expect("Cannot write frame");
+    println!("Wrote {} frames to {}/{}_*", frames.frames_written(), dir, prefix);
+}
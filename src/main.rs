@@ -1,11 +1,13 @@
 use std::time::Instant;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use thousands::Separable;
 
 
 #[derive(Parser)]
 struct Opts {
+    #[clap(subcommand)]
+    command: Option<Command>,
     #[clap(short, default_value="1")]
     repeat: i32,
     day: Option<i32>,
@@ -15,9 +17,41 @@ struct Opts {
     per: bool,
     #[clap(short, long)]
     test: bool,
+    /// Plays the day's simulation in the terminal (days 11, 13, 20 and 25)
+    #[clap(long)]
+    animate: bool,
+    /// Frames per second when animating
+    #[clap(long, default_value="10")]
+    fps: f32,
+    /// Writes the day's intermediate states as PGM/PPM frames into DIR (days 5, 9, 15, 20, 22 and 25)
+    #[clap(long, value_name="DIR")]
+    export: Option<String>,
+    /// Pixels per grid cell in exported frames
+    #[clap(long, default_value="4")]
+    scale: usize,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solves inputs POSTed to http://127.0.0.1:PORT/2021/day/NN
+    Serve(serve::ServeOpts),
+    /// Loads a day's input to step through and query interactively
+    Repl(repl::ReplOpts),
+    /// Counts depth increases over sliding windows of readings, streamed from a file or stdin
+    Sonar(day01::SonarOpts),
+    /// Replays submarine course files, reporting where each ends up and where they diverge
+    Course(day02::CourseOpts),
+    /// Reads a diagnostic report of any bit width, from a file or stdin, and rates it
+    Diagnostic(day03::DiagnosticOpts),
+    /// Ranks bingo boards of any size by when they win, under rule variants
+    Bingo(day04::BingoOpts),
 }
 
 mod utils;
+mod serve;
+mod repl;
+mod animate;
+mod export;
 mod day01;
 mod day02;
 mod day03;
@@ -43,7 +77,7 @@ mod day22;
 mod day23;
 mod day24;
 mod day25;
-static DAYS: &'static [fn(bool, bool)] = &[
+static DAYS: &[fn(bool, bool)] = &[
     day01::day01,
     day02::day02,
     day03::day03,
@@ -71,9 +105,75 @@ static DAYS: &'static [fn(bool, bool)] = &[
     day25::day25,
 ];
 
+// Solves a day from its input text, returning the answers as strings.
+pub type Solver = fn(&str) -> (String, Option<String>);
+
+macro_rules! solver {
+    ($day:ident) => {
+        |input: &str| {
+            let (part1, part2) = $day::solve(input);
+            (part1.to_string(), Some(part2.to_string()))
+        }
+    };
+}
+
+static SOLVERS: &[Solver] = &[
+    solver!(day01),
+    solver!(day02),
+    solver!(day03),
+    |input: &str| match day04::solve(input) {
+        Some((first, last)) => (first.to_string(), Some(last.to_string())),
+        None => ("no board wins".to_string(), None),
+    },
+    solver!(day05),
+    solver!(day06),
+    solver!(day07),
+    solver!(day08),
+    solver!(day09),
+    solver!(day10),
+    solver!(day11),
+    solver!(day12),
+    solver!(day13),
+    solver!(day14),
+    solver!(day15),
+    solver!(day16),
+    solver!(day17),
+    solver!(day18),
+    solver!(day19),
+    solver!(day20),
+    solver!(day21),
+    solver!(day22),
+    solver!(day23),
+    solver!(day24),
+    |input: &str| (day25::solve(input).to_string(), None),
+];
+
 fn main() {
     let opts = Opts::parse();
-    assert!(opts.repeat == 1 || opts.atleast == None);
+    match &opts.command {
+        Some(Command::Serve(serve_opts)) => return serve::serve(serve_opts, SOLVERS),
+        Some(Command::Repl(repl_opts)) => return repl::repl(repl_opts, SOLVERS),
+        Some(Command::Sonar(sonar_opts)) => return day01::sonar(sonar_opts),
+        Some(Command::Course(course_opts)) => return day02::course(course_opts),
+        Some(Command::Diagnostic(diagnostic_opts)) => return day03::diagnostic(diagnostic_opts),
+        Some(Command::Bingo(bingo_opts)) => return day04::bingo(bingo_opts),
+        None => {}
+    }
+    if opts.animate {
+        match opts.day {
+            Some(day) => animate::animate(day as usize, opts.fps),
+            None => println!("Pick a day to animate"),
+        }
+        return;
+    }
+    if let Some(dir) = &opts.export {
+        match opts.day {
+            Some(day) => export::export(day as usize, dir, opts.scale),
+            None => println!("Pick a day to export"),
+        }
+        return;
+    }
+    assert!(opts.repeat == 1 || opts.atleast.is_none());
     println!("Hello, world!");
     match opts.day {
         Some(day) => println!("Day {}", day),
@@ -84,11 +184,11 @@ fn main() {
         // Benchmarks per-day.
         let atleast = opts.atleast.unwrap_or(0.5);
         let mut total = 0f64;
-        for i in 0..DAYS.len() {
+        for (i, run_day) in DAYS.iter().enumerate() {
             let started = Instant::now();
             let mut samples = 0;
             while started.elapsed().as_secs_f32() < atleast {
-                DAYS[i](opts.test, false);
+                run_day(opts.test, false);
                 samples += 1;
             }
             let elapsed = started.elapsed();
@@ -0,0 +1,112 @@
+// Interactive exploration of a day's parsed state, for debugging without sprinkling prints.
+//
+// Days that have something worth stepping through provide a `Session`; every day gets
+// the built-in commands (help, solve, quit).
+
+use std::io::{BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+
+use clap::Args;
+
+use crate::Solver;
+
+#[derive(Args)]
+pub struct ReplOpts {
+    /// Day to load
+    day: usize,
+    /// Input file (defaults to inputs/inputNN.txt)
+    #[clap(long)]
+    input: Option<String>,
+}
+
+pub trait Session {
+    // One line per command, "name args   description".
+    fn help(&self) -> &'static str;
+
+    fn run(&mut self, cmd: &str, args: &[&str]) -> Result<String, String>;
+}
+
+pub fn unknown(cmd: &str) -> String {
+    format!("Unknown command: {}  (try \"help\")", cmd)
+}
+
+// Parses args[idx], falling back to the default when it's missing.
+pub fn arg<T: FromStr>(args: &[&str], idx: usize, default: Option<T>) -> Result<T, String> {
+    match (args.get(idx), default) {
+        (Some(s), _) => s.parse().map_err(|_| format!("Bad argument: {}", s)),
+        (None, Some(value)) => Ok(value),
+        (None, None) => Err(format!("Missing argument {}", idx + 1)),
+    }
+}
+
+fn session_for(day: usize, input_str: &str) -> Option<Box<dyn Session>> {
+    match day {
+        11 => Some(Box::new(crate::day11::OctopusSession::new(input_str))),
+        16 => Some(Box::new(crate::day16::PacketSession::new(input_str))),
+        23 => Some(Box::new(crate::day23::SearchSession::new(input_str))),
+        24 => Some(Box::new(crate::day24::AluSession::new(input_str))),
+        _ => None,
+    }
+}
+
+const BUILTIN_HELP: &str = "\
+help                show this
+solve               run the full solver on the input
+quit";
+
+pub fn repl(opts: &ReplOpts, solvers: &[Solver]) {
+    if !(1..=solvers.len()).contains(&opts.day) {
+        println!("No such day: {}", opts.day);
+        return;
+    }
+    let path = opts.input.clone().unwrap_or_else(|| format!("inputs/input{:02}.txt", opts.day));
+    let input_str = std::fs::read_to_string(&path).expect("Cannot open file");
+    let mut session = session_for(opts.day, &input_str);
+    println!("Loaded {} ({} lines)", path, input_str.lines().count());
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("day{:02}> ", opts.day);
+        std::io::stdout().flush().unwrap();
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (cmd, args) = match words.split_first() {
+            Some((cmd, args)) => (*cmd, args),
+            None => continue,
+        };
+
+        if cmd == "quit" || cmd == "exit" {
+            break;
+        }
+
+        // A bad command shouldn't throw away the session.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| match cmd {
+            "help" => {
+                let mut help = BUILTIN_HELP.to_string();
+                if let Some(session) = &session {
+                    help.push('\n');
+                    help.push_str(session.help());
+                }
+                Ok(help)
+            }
+            "solve" => {
+                let (part1, part2) = solvers[opts.day - 1](&input_str);
+                Ok(format!("Part 1: {}\nPart 2: {}", part1, part2.unwrap_or_default()))
+            }
+            _ => match &mut session {
+                Some(session) => session.run(cmd, args),
+                None => Err(unknown(cmd)),
+            },
+        }));
+        match result {
+            Ok(Ok(out)) => println!("{}", out.trim_end()),
+            Ok(Err(err)) => println!("{}", err),
+            Err(_) => println!("Command panicked"),
+        }
+    }
+}
@@ -0,0 +1,339 @@
+// A tiny HTTP front-end for the solvers, so other tools can use them without linking Rust.
+//
+//   POST /2021/day/NN   (body is the puzzle input)
+//
+// responds with something like:
+//
+//   {"day":5,"part1":"8111","part2":"22088","solve_us":10388}
+//
+// Only binds to localhost. Connections are handed to a fixed pool of workers through a
+// bounded queue, and anything past the queue gets a 503 instead of piling up. Solves run on
+// their own thread so a worker can give up on one with a 504 after --timeout; a thread can't
+// be killed, so only so many abandoned solves may keep running before new ones get a 503.
+// Requests that take too long to arrive, even a byte at a time, get a 408.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, sync_channel, Receiver, RecvTimeoutError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+
+use crate::Solver;
+
+#[derive(Args, Clone)]
+pub struct ServeOpts {
+    /// Port to listen on (always on 127.0.0.1)
+    #[clap(long, default_value = "2021")]
+    port: u16,
+    /// Number of requests solved at once
+    #[clap(long, default_value = "4")]
+    workers: usize,
+    /// Connections allowed to wait for a worker before getting a 503
+    #[clap(long, default_value = "16")]
+    queue: usize,
+    /// Largest accepted request body, in bytes
+    #[clap(long, default_value = "1048576")]
+    max_body: usize,
+    /// Seconds a solve may take before the request gets a 504
+    #[clap(long, default_value = "10")]
+    timeout: f32,
+}
+
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+// IO_TIMEOUT only bounds each read, so a client trickling bytes in could otherwise hold a
+// worker for as long as it likes.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+// Overflow 503s are written off the accept thread, by at most this many threads at a time
+// and with a short timeout; past that, overflow connections are just closed.
+const MAX_REJECTING: usize = 16;
+const REJECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Shared by the workers: how long a solve may run, and how many timed-out solves are still
+// running in the background.
+struct Limits {
+    max_body: usize,
+    timeout: Duration,
+    max_abandoned: usize,
+    abandoned: Arc<AtomicUsize>,
+}
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, reason: &'static str, body: String) -> Self {
+        Response{status, reason, body}
+    }
+
+    fn error(status: u16, reason: &'static str, message: &str) -> Self {
+        Response::json(status, reason, format!("{{\"error\":\"{}\"}}", json_escape(message)))
+    }
+
+    fn write_to(&self, stream: &mut TcpStream) -> std::io::Result<()> {
+        write!(stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status, self.reason, self.body.len(), self.body)?;
+        stream.flush()
+    }
+}
+
+// Reads from the stream until the deadline, and then fails every read with TimedOut.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "request deadline passed"));
+        }
+        self.stream.set_read_timeout(Some(left.min(IO_TIMEOUT)))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+fn is_timeout(err: &std::io::Error) -> bool {
+    matches!(err.kind(), std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock)
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// "/2021/day/5" or "/2021/day/05" -> 5
+fn parse_day_path(path: &str, num_days: usize) -> Option<usize> {
+    let day = path.strip_prefix("/2021/day/")?;
+    if day.is_empty() || day.len() > 2 || !day.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let day: usize = day.parse().ok()?;
+    if (1..=num_days).contains(&day) { Some(day) } else { None }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "solver panicked".to_string()
+    }
+}
+
+fn solve_day(solver: Solver, day: usize, mut input: String, limits: &Limits) -> Response {
+    // The inputs on disk always end in a newline, and some parsers count on it.
+    if !input.ends_with('\n') {
+        input.push('\n');
+    }
+
+    if limits.abandoned.load(Ordering::SeqCst) >= limits.max_abandoned {
+        return Response::error(503, "Service Unavailable", "too many solves still running");
+    }
+
+    // The solver thread and the worker agree on who owns the result under this lock: the
+    // solver sends it unless the worker already gave up, in which case the solver uncounts
+    // itself from the abandoned ones.
+    let gave_up = Arc::new(Mutex::new(false));
+    let (done, result) = channel();
+    let started = Instant::now();
+    {
+        let gave_up = Arc::clone(&gave_up);
+        let abandoned = Arc::clone(&limits.abandoned);
+        thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| solver(&input)));
+            let gave_up = gave_up.lock().unwrap();
+            if *gave_up {
+                abandoned.fetch_sub(1, Ordering::SeqCst);
+            } else {
+                let _ = done.send((result, started.elapsed()));
+            }
+        });
+    }
+
+    let (result, elapsed) = match result.recv_timeout(limits.timeout) {
+        Ok(finished) => finished,
+        Err(RecvTimeoutError::Timeout) => {
+            let mut gave_up = gave_up.lock().unwrap();
+            // It may have finished between the timeout and taking the lock.
+            match result.try_recv() {
+                Ok(finished) => finished,
+                Err(_) => {
+                    *gave_up = true;
+                    limits.abandoned.fetch_add(1, Ordering::SeqCst);
+                    return Response::error(504, "Gateway Timeout",
+                        &format!("day {} took longer than {:?}", day, limits.timeout));
+                }
+            }
+        }
+        Err(RecvTimeoutError::Disconnected) => unreachable!("solver thread always sends"),
+    };
+
+    match result {
+        Ok((part1, part2)) => {
+            let part2 = match part2 {
+                Some(answer) => format!("\"{}\"", json_escape(&answer)),
+                None => "null".to_string(),
+            };
+            Response::json(200, "OK", format!(
+                "{{\"day\":{},\"part1\":\"{}\",\"part2\":{},\"solve_us\":{}}}",
+                day, json_escape(&part1), part2, elapsed.as_micros()))
+        }
+        Err(payload) => Response::error(422, "Unprocessable Entity",
+            &format!("could not solve day {}: {}", day, panic_message(payload.as_ref()))),
+    }
+}
+
+fn handle(stream: &mut TcpStream, solvers: &[Solver], limits: &Limits) -> Response {
+    let max_body = limits.max_body;
+    let deadline = DeadlineReader{stream, deadline: Instant::now() + REQUEST_TIMEOUT};
+    let mut reader = BufReader::new(deadline.take((MAX_HEADER_BYTES + max_body) as u64));
+
+    // Request line and headers
+    let mut head_bytes = 0;
+    let mut lines = Vec::<String>::with_capacity(16);
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => return Response::error(400, "Bad Request", "connection closed mid-request"),
+            Ok(n) => head_bytes += n,
+            Err(err) if is_timeout(&err) => return Response::error(408, "Request Timeout", "request took too long to arrive"),
+            Err(_) => return Response::error(400, "Bad Request", "could not read request"),
+        }
+        if head_bytes > MAX_HEADER_BYTES {
+            return Response::error(431, "Request Header Fields Too Large", "headers too large");
+        }
+        let line = line.trim_end_matches(['\r', '\n']).to_string();
+        if line.is_empty() {
+            break;
+        }
+        lines.push(line);
+    }
+
+    let mut request_line = lines.first().map(String::as_str).unwrap_or("").split(' ');
+    let method = request_line.next().unwrap_or("");
+    let path = request_line.next().unwrap_or("");
+
+    let day = match parse_day_path(path, solvers.len()) {
+        Some(day) => day,
+        None => return Response::error(404, "Not Found", "expected /2021/day/NN"),
+    };
+    if method != "POST" {
+        return Response::error(405, "Method Not Allowed", "only POST is supported");
+    }
+
+    let mut content_length = None;
+    for header in lines.iter().skip(1) {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let content_length = match content_length {
+        Some(len) => len,
+        None => return Response::error(411, "Length Required", "Content-Length is required"),
+    };
+    if content_length > max_body {
+        return Response::error(413, "Payload Too Large",
+            &format!("input is {} bytes, limit is {}", content_length, max_body));
+    }
+
+    let mut body = vec![0u8; content_length];
+    match reader.read_exact(&mut body) {
+        Ok(()) => {}
+        Err(err) if is_timeout(&err) => return Response::error(408, "Request Timeout", "request took too long to arrive"),
+        Err(_) => return Response::error(400, "Bad Request", "body shorter than Content-Length"),
+    }
+    let input = match String::from_utf8(body) {
+        Ok(input) => input,
+        Err(_) => return Response::error(400, "Bad Request", "input must be UTF-8"),
+    };
+
+    solve_day(solvers[day - 1], day, input, limits)
+}
+
+fn worker(queue: Arc<Mutex<Receiver<TcpStream>>>, solvers: &'static [Solver], limits: Arc<Limits>) {
+    loop {
+        let next = queue.lock().unwrap().recv();
+        let mut stream = match next {
+            Ok(stream) => stream,
+            Err(_) => return,  // Listener is gone
+        };
+        let response = handle(&mut stream, solvers, &limits);
+        let _ = response.write_to(&mut stream);
+    }
+}
+
+pub fn serve(opts: &ServeOpts, solvers: &'static [Solver]) {
+    let listener = TcpListener::bind(("127.0.0.1", opts.port)).expect("Cannot bind");
+    println!("Serving {} days on http://127.0.0.1:{}/2021/day/NN  ({} workers, queue of {})",
+        solvers.len(), opts.port, opts.workers, opts.queue);
+
+    let workers = opts.workers.max(1);
+    let limits = Arc::new(Limits{
+        max_body: opts.max_body,
+        timeout: Duration::from_secs_f32(opts.timeout.max(0.001)),
+        max_abandoned: workers,
+        abandoned: Arc::new(AtomicUsize::new(0)),
+    });
+    let (sender, receiver) = sync_channel::<TcpStream>(opts.queue);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..workers {
+        let receiver = Arc::clone(&receiver);
+        let limits = Arc::clone(&limits);
+        thread::spawn(move || worker(receiver, solvers, limits));
+    }
+
+    let rejecting = Arc::new(AtomicUsize::new(0));
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+        let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+
+        match sender.try_send(stream) {
+            Ok(()) => {}
+            Err(TrySendError::Full(mut stream)) => {
+                if rejecting.fetch_add(1, Ordering::SeqCst) >= MAX_REJECTING {
+                    rejecting.fetch_sub(1, Ordering::SeqCst);
+                    continue;  // Dropping it closes the connection
+                }
+                let rejecting = Arc::clone(&rejecting);
+                thread::spawn(move || {
+                    let _ = stream.set_write_timeout(Some(REJECT_TIMEOUT));
+                    let _ = Response::error(503, "Service Unavailable", "too many requests")
+                        .write_to(&mut stream);
+                    rejecting.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+            Err(TrySendError::Disconnected(_)) => break,
+        }
+    }
+}
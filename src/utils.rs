@@ -1,76 +1,45 @@
-use std::{ops::{Index, IndexMut}, cmp::max};
+use std::ops::{Index, IndexMut};
+
+mod bit_grid;
+mod cuboid;
+mod disjoint_set;
+mod grid3;
+mod interval;
+mod iter_ext;
+mod memo;
+mod ocr;
+mod parse;
+mod pnm;
+mod point;
+mod rotation;
+mod scan;
+mod search;
+mod sparse_grid;
+mod table;
+mod transform;
+pub use bit_grid::*;
+pub use cuboid::*;
+pub use disjoint_set::*;
+pub use grid3::*;
+pub use interval::*;
+pub use iter_ext::*;
+pub use memo::*;
+pub use ocr::*;
+#[allow(unused_imports)]
+pub use parse::*;
+pub use pnm::*;
+pub use point::*;
+pub use rotation::*;
+pub use scan::*;
+pub use search::*;
+pub use sparse_grid::*;
+pub use table::*;
 
 #[inline]
 pub fn abs_diff(a: usize, b: usize) -> usize {
-    if a > b { a - b } else { b - a }
+    a.abs_diff(b)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Pt {
-    pub x: i32,
-    pub y: i32,
-}
-
-impl Pt {
-    pub fn at(x: i32, y: i32) -> Pt {
-        Pt{x, y}
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Pt3 {
-    pub x: i32,
-    pub y: i32,
-    pub z: i32,
-}
-
-impl Pt3 {
-    pub fn new(x: i32, y: i32, z: i32) -> Self {
-        Self{x, y, z}
-    }
-
-    pub fn dist_to(&self, p: &Pt3) -> f32 {
-        (((self.x - p.x).pow(2) +
-          (self.y - p.y).pow(2) +
-          (self.z - p.z).pow(2)) as f32).sqrt()
-    }
-
-    pub fn dist_to_sqr(&self, p: &Pt3) -> i32 {
-        (self.x - p.x).pow(2) +
-        (self.y - p.y).pow(2) +
-        (self.z - p.z).pow(2)
-    }
-
-    pub fn l1_to(&self, p: &Pt3) -> i32 {
-        (self.x - p.x).abs() +
-        (self.y - p.y).abs() +
-        (self.z - p.z).abs()
-    }
-}
-
-impl std::fmt::Display for Pt3 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<{}, {}, {}>", self.x, self.y, self.z)
-    }
-}
-
-impl std::ops::Add for &Pt3 {
-    type Output = Pt3;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Pt3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
-    }
-}
-
-impl std::ops::Sub for &Pt3 {
-    type Output = Pt3;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Pt3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
-    }
-}
-
-
 pub struct Grid<T> {
     pub rows: usize,
     pub cols: usize,
@@ -81,7 +50,7 @@ impl<T> Grid<T> {
     pub fn filled(rows: usize, cols: usize, value: T) -> Grid<T>
         where T: Clone
     {
-        Grid{rows: rows, cols: cols, data: vec![value; rows * cols]}
+        Grid{rows, cols, data: vec![value; rows * cols]}
     }
 
     #[allow(dead_code)]
@@ -98,7 +67,6 @@ impl<T> Grid<T> {
         out
     }
 
-    #[allow(dead_code)]
     pub fn as_strings(&self) -> Grid<String>
         where T: std::fmt::Display
     {
@@ -118,6 +86,125 @@ impl<T> Grid<T> {
         let table = self.as_strings();
         tabulate(&table)
     }
+
+    // Each cell in the SGR style chosen from its (row, col) and value, if any.
+    pub fn fmt_table_styled<'a>(&self, style: impl Fn((usize, usize), &T) -> Option<&'a str>) -> String
+        where T: std::fmt::Display
+    {
+        tabulate_styled(&self.as_strings(), |rc| style(rc, &self[rc]))
+    }
+
+    // The given cells in one SGR style, and the rest plain.
+    pub fn fmt_table_highlighted(&self, cells: impl IntoIterator<Item = (usize, usize)>, sgr: &str) -> String
+        where T: std::fmt::Display
+    {
+        let mut marked = Grid::filled(self.rows, self.cols, false);
+        for rc in cells {
+            marked[rc] = true;
+        }
+        tabulate_styled(&self.as_strings(), |rc| marked[rc].then_some(sgr))
+    }
+}
+
+// Neighbor offsets as (dr, dc), in reading order.
+const OFFSETS4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const OFFSETS8: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+// Neighboring positions are plain (row, col) iterators that don't borrow the grid, so
+// they can be used while updating it.
+impl<T> Grid<T> {
+    #[inline]
+    pub fn get(&self, r: usize, c: usize) -> Option<&T> {
+        if r < self.rows && c < self.cols { Some(&self.data[r * self.cols + c]) } else { None }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, r: usize, c: usize) -> Option<&mut T> {
+        if r < self.rows && c < self.cols { Some(&mut self.data[r * self.cols + c]) } else { None }
+    }
+
+    // Wraps around the edges, so (-1, -1) is the bottom right corner.
+    #[inline]
+    pub fn get_wrapping(&self, r: isize, c: isize) -> &T {
+        let r = r.rem_euclid(self.rows as isize) as usize;
+        let c = c.rem_euclid(self.cols as isize) as usize;
+        &self.data[r * self.cols + c]
+    }
+
+    // The bounds checks are unrolled, one per side, since these sit in inner loops.
+    #[inline]
+    pub fn adjacent4(&self, r: usize, c: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (up, left, right, down) = (r > 0, c > 0, c + 1 < self.cols, r + 1 < self.rows);
+        [
+            up.then_some((r.wrapping_sub(1), c)),
+            left.then_some((r, c.wrapping_sub(1))),
+            right.then_some((r, c + 1)),
+            down.then_some((r + 1, c)),
+        ].into_iter().flatten()
+    }
+
+    #[inline]
+    pub fn adjacent8(&self, r: usize, c: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (up, left, right, down) = (r > 0, c > 0, c + 1 < self.cols, r + 1 < self.rows);
+        let (ru, cl) = (r.wrapping_sub(1), c.wrapping_sub(1));
+        [
+            (up && left).then_some((ru, cl)),
+            up.then_some((ru, c)),
+            (up && right).then_some((ru, c + 1)),
+            left.then_some((r, cl)),
+            right.then_some((r, c + 1)),
+            (down && left).then_some((r + 1, cl)),
+            down.then_some((r + 1, c)),
+            (down && right).then_some((r + 1, c + 1)),
+        ].into_iter().flatten()
+    }
+
+    pub fn adjacent4_wrapping(&self, r: usize, c: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (rows, cols) = (self.rows as isize, self.cols as isize);
+        OFFSETS4.into_iter().map(move |(dr, dc)| {
+            ((r as isize + dr).rem_euclid(rows) as usize, (c as isize + dc).rem_euclid(cols) as usize)
+        })
+    }
+
+    pub fn adjacent8_wrapping(&self, r: usize, c: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (rows, cols) = (self.rows as isize, self.cols as isize);
+        OFFSETS8.into_iter().map(move |(dr, dc)| {
+            ((r as isize + dr).rem_euclid(rows) as usize, (c as isize + dc).rem_euclid(cols) as usize)
+        })
+    }
+
+    pub fn neighbors4(&self, r: usize, c: usize) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.adjacent4(r, c).map(move |rc| (rc, &self[rc]))
+    }
+
+    pub fn neighbors8(&self, r: usize, c: usize) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.adjacent8(r, c).map(move |rc| (rc, &self[rc]))
+    }
+
+    pub fn neighbors4_wrapping(&self, r: usize, c: usize) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.adjacent4_wrapping(r, c).map(move |rc| (rc, &self[rc]))
+    }
+
+    pub fn neighbors8_wrapping(&self, r: usize, c: usize) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.adjacent8_wrapping(r, c).map(move |rc| (rc, &self[rc]))
+    }
+
+    pub fn row(&self, r: usize) -> &[T] {
+        &self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, r: usize) -> &mut [T] {
+        &mut self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn col(&self, c: usize) -> impl Iterator<Item = &T> {
+        self.data[c..].iter().step_by(self.cols)
+    }
+
+    pub fn col_mut(&mut self, c: usize) -> impl Iterator<Item = &mut T> {
+        let cols = self.cols;
+        self.data[c..].iter_mut().step_by(cols)
+    }
 }
 
 impl Grid<u8> {
@@ -182,29 +269,6 @@ impl<T: Clone> Clone for Grid<T> {
     }
 }
 
-#[allow(dead_code)]
-pub fn tabulate(table: &Grid<String>) -> String {
-    let mut lengths = vec![0; table.cols];
-    for i in 0..table.data.len() {
-        let c = i % table.cols;
-        lengths[c] = max(lengths[c], table.data[i].len());
-        // TODO: ^^ Not correct for unicode.
-    }
-
-    let mut out = String::with_capacity(table.rows * (lengths.iter().sum::<usize>() + table.cols));
-    for i in 0..table.data.len() {
-        let c = i % table.cols;
-        out.push_str(&str::repeat(" ", lengths[c] - table.data[i].len()));
-        out.push_str(&table.data[i]);
-        if c == table.cols - 1 {
-            out.push('\n');
-        } else {
-            out.push(' ');
-        }
-    }
-    out
-}
-
 // Ordering by the first element of a tuple (in reverse)
 #[repr(transparent)]
 #[derive(Debug)]
@@ -0,0 +1,398 @@
+// A grid of bools packed 64 to a word, for cellular automata.
+//
+// Each row starts on a fresh word, and column c of a row is bit c % 64 of word c / 64.
+// Bits past the last column are always kept clear, so whole-word operations (counts,
+// comparisons, shifts) can ignore the row length.
+
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use super::Grid;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    pub rows: usize,
+    pub cols: usize,
+    words_per_row: usize,
+    data: Vec<u64>,
+}
+
+// dst[c] = src[c - n], with zeros shifted in. The slices can differ in length.
+fn shift_bits_up(src: &[u64], n: usize, dst: &mut [u64]) {
+    let (words, bits) = (n / 64, n % 64);
+    for i in 0..dst.len() {
+        let whole = if i >= words && i - words < src.len() { src[i - words] << bits } else { 0 };
+        let carry = if bits > 0 && i > words && i - words - 1 < src.len() { src[i - words - 1] >> (64 - bits) } else { 0 };
+        dst[i] = whole | carry;
+    }
+}
+
+// dst[c] = src[c + n], with zeros shifted in.
+fn shift_bits_down(src: &[u64], n: usize, dst: &mut [u64]) {
+    let (words, bits) = (n / 64, n % 64);
+    for i in 0..dst.len() {
+        let whole = if i + words < src.len() { src[i + words] >> bits } else { 0 };
+        let carry = if bits > 0 && i + words + 1 < src.len() { src[i + words + 1] << (64 - bits) } else { 0 };
+        dst[i] = whole | carry;
+    }
+}
+
+impl BitGrid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words_per_row = cols.div_ceil(64);
+        BitGrid{rows, cols, words_per_row, data: vec![0; rows * words_per_row]}
+    }
+
+    pub fn filled(rows: usize, cols: usize, value: bool) -> Self {
+        let mut grid = BitGrid::new(rows, cols);
+        if value {
+            grid.data.fill(!0);
+            grid.clear_tails();
+        }
+        grid
+    }
+
+    pub fn from_grid<T>(grid: &Grid<T>, is_set: impl Fn(&T) -> bool) -> Self {
+        let mut bits = BitGrid::new(grid.rows, grid.cols);
+        for r in 0..grid.rows {
+            for (c, value) in grid.row(r).iter().enumerate() {
+                if is_set(value) {
+                    bits.data[r * bits.words_per_row + c / 64] |= 1 << (c % 64);
+                }
+            }
+        }
+        bits
+    }
+
+    pub fn to_grid<T: Clone>(&self, on: T, off: T) -> Grid<T> {
+        let mut data = Vec::with_capacity(self.rows * self.cols);
+        for r in 0..self.rows {
+            data.extend((0..self.cols).map(|c| if self.get(r, c) { on.clone() } else { off.clone() }));
+        }
+        Grid{rows: self.rows, cols: self.cols, data}
+    }
+
+    fn tail_mask(&self) -> u64 {
+        match self.cols % 64 {
+            0 => !0,
+            used => (1 << used) - 1,
+        }
+    }
+
+    fn clear_tails(&mut self) {
+        if self.words_per_row == 0 {
+            return;
+        }
+        let mask = self.tail_mask();
+        for last in self.data.iter_mut().skip(self.words_per_row - 1).step_by(self.words_per_row) {
+            *last &= mask;
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        (self.data[r * self.words_per_row + c / 64] >> (c % 64)) & 1 == 1
+    }
+
+    // `outside` for anything off the grid, including indices that wrapped below zero.
+    #[inline]
+    pub fn get_or(&self, r: usize, c: usize, outside: bool) -> bool {
+        if r < self.rows && c < self.cols { self.get(r, c) } else { outside }
+    }
+
+    #[inline]
+    pub fn set(&mut self, r: usize, c: usize, value: bool) {
+        let word = &mut self.data[r * self.words_per_row + c / 64];
+        if value {
+            *word |= 1 << (c % 64);
+        } else {
+            *word &= !(1 << (c % 64));
+        }
+    }
+
+    pub fn row_words(&self, r: usize) -> &[u64] {
+        &self.data[r * self.words_per_row..(r + 1) * self.words_per_row]
+    }
+
+    // Callers must leave the bits past the last column clear.
+    pub fn row_words_mut(&mut self, r: usize) -> &mut [u64] {
+        &mut self.data[r * self.words_per_row..(r + 1) * self.words_per_row]
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.data.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn any(&self) -> bool {
+        self.data.iter().any(|w| *w != 0)
+    }
+
+    // The set cells as (row, col), in reading order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.data.iter().enumerate().flat_map(move |(i, &word)| {
+            let (r, base) = (i / self.words_per_row, i % self.words_per_row * 64);
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                Some((r, base + bit))
+            })
+        })
+    }
+
+    // Moves every cell dc columns right (left if negative). Cells pushed off an edge are
+    // dropped, and the gap is left clear.
+    pub fn shift_cols(&self, dc: isize) -> BitGrid {
+        let mut out = BitGrid::new(self.rows, self.cols);
+        let n = dc.unsigned_abs();
+        for r in 0..self.rows {
+            let (src, dst) = (self.row_words(r), &mut out.data[r * self.words_per_row..(r + 1) * self.words_per_row]);
+            if dc >= 0 { shift_bits_up(src, n, dst) } else { shift_bits_down(src, n, dst) }
+        }
+        out.clear_tails();
+        out
+    }
+
+    // Like shift_cols, but cells pushed off one edge come back in on the other.
+    pub fn shift_cols_wrapping(&self, dc: isize) -> BitGrid {
+        if self.cols == 0 {
+            return self.clone();
+        }
+        let right = dc.rem_euclid(self.cols as isize);
+        let mut out = self.shift_cols(right);
+        out |= &self.shift_cols(right - self.cols as isize);
+        out
+    }
+
+    // Moves every row dr rows down (up if negative), leaving the gap clear.
+    pub fn shift_rows(&self, dr: isize) -> BitGrid {
+        let mut out = BitGrid::new(self.rows, self.cols);
+        let n = dr.unsigned_abs().min(self.rows) * self.words_per_row;
+        if dr >= 0 {
+            out.data[n..].copy_from_slice(&self.data[..self.data.len() - n]);
+        } else {
+            let len = self.data.len();
+            out.data[..len - n].copy_from_slice(&self.data[n..]);
+        }
+        out
+    }
+
+    pub fn shift_rows_wrapping(&self, dr: isize) -> BitGrid {
+        if self.rows == 0 {
+            return self.clone();
+        }
+        let mut out = self.clone();
+        out.data.rotate_right(dr.rem_euclid(self.rows as isize) as usize * self.words_per_row);
+        out
+    }
+
+    // The 3x3 window around (r, c) as a 9-bit index: the top row in the high bits, and
+    // the left column highest within each row. Cells off the grid read as `outside`.
+    pub fn window3(&self, r: usize, c: usize, outside: bool) -> usize {
+        let mut index = 0;
+        for wr in [r.wrapping_sub(1), r, r.wrapping_add(1)] {
+            for wc in [c.wrapping_sub(1), c, c.wrapping_add(1)] {
+                index = index << 1 | self.get_or(wr, wc, outside) as usize;
+            }
+        }
+        index
+    }
+
+    // How many of the 8 cells around (r, c) are set.
+    pub fn count_neighbors8(&self, r: usize, c: usize, outside: bool) -> u32 {
+        (self.window3(r, c, outside) & !0b000_010_000).count_ones()
+    }
+
+    // One step of a 3x3 automaton. The result is `margin` cells bigger on every side, and
+    // each of its cells is rule(window index) for the matching window of this grid (which
+    // reads as `outside` beyond its edges).
+    pub fn map_windows3(&self, margin: usize, outside: bool, rule: impl Fn(usize) -> bool) -> BitGrid {
+        let mut out = BitGrid::new(self.rows + 2 * margin, self.cols + 2 * margin);
+
+        // Input rows get copied out with enough room either side for every window, so bit j
+        // of a padded row is input column j - margin - 1.
+        let width = out.cols + 2;
+        let words = width.div_ceil(64);
+        let mut off_grid = vec![0u64; words];
+        let mut blank = vec![0u64; words];
+        if outside {
+            for j in (0..width).filter(|j| *j <= margin || *j > margin + self.cols) {
+                off_grid[j / 64] |= 1 << (j % 64);
+            }
+            for j in 0..width {
+                blank[j / 64] |= 1 << (j % 64);
+            }
+        }
+        let pad = |r: usize, dst: &mut Vec<u64>| {
+            if r < self.rows {
+                shift_bits_up(self.row_words(r), margin + 1, dst);
+                for (d, o) in dst.iter_mut().zip(&off_grid) {
+                    *d |= o;
+                }
+            } else {
+                dst.copy_from_slice(&blank);
+            }
+        };
+        // Bits j..j + 128 of a padded row, for the word of output columns starting at j.
+        let span = |row: &[u64], w: usize| row[w] as u128 | (row.get(w + 1).copied().unwrap_or(0) as u128) << 64;
+
+        let (mut top, mut mid, mut bottom) = (vec![0; words], vec![0; words], vec![0; words]);
+        pad(0usize.wrapping_sub(margin + 1), &mut mid);
+        pad(0usize.wrapping_sub(margin), &mut bottom);
+        for r in 0..out.rows {
+            // Moves the window down a row.
+            std::mem::swap(&mut top, &mut mid);
+            std::mem::swap(&mut mid, &mut bottom);
+            pad(r.wrapping_sub(margin).wrapping_add(1), &mut bottom);
+            // Slides the window right one column at a time, as in window3(). The spans are
+            // shifted down as they're used, so the next column is always bit 0.
+            let mut index = 0;
+            for w in 0..out.words_per_row {
+                let (mut t, mut m, mut b) = (span(&top, w), span(&mid, w), span(&bottom, w));
+                if w == 0 {
+                    for _ in 0..2 {
+                        index = (index << 1) & 0b110_110_110 | ((t & 1) << 6 | (m & 1) << 3 | (b & 1)) as usize;
+                        (t, m, b) = (t >> 1, m >> 1, b >> 1);
+                    }
+                } else {
+                    (t, m, b) = (t >> 2, m >> 2, b >> 2);
+                }
+                let mut word = 0u64;
+                for k in 0..(out.cols - 64 * w).min(64) {
+                    index = (index << 1) & 0b110_110_110 | ((t & 1) << 6 | (m & 1) << 3 | (b & 1)) as usize;
+                    (t, m, b) = (t >> 1, m >> 1, b >> 1);
+                    word |= (rule(index) as u64) << k;
+                }
+                out.data[r * out.words_per_row + w] = word;
+            }
+        }
+        out
+    }
+}
+
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        let mut out = self.clone();
+        for word in &mut out.data {
+            *word = !*word;
+        }
+        out.clear_tails();
+        out
+    }
+}
+
+macro_rules! bit_grid_op {
+    ($op:ident, $method:ident, $assign_op:ident, $assign_method:ident) => {
+        impl $assign_op<&BitGrid> for BitGrid {
+            fn $assign_method(&mut self, rhs: &BitGrid) {
+                assert_eq!((self.rows, self.cols), (rhs.rows, rhs.cols), "BitGrid sizes differ");
+                for (a, b) in self.data.iter_mut().zip(&rhs.data) {
+                    a.$assign_method(*b);
+                }
+            }
+        }
+
+        impl $op for &BitGrid {
+            type Output = BitGrid;
+
+            fn $method(self, rhs: &BitGrid) -> BitGrid {
+                let mut out = self.clone();
+                out.$assign_method(rhs);
+                out
+            }
+        }
+    };
+}
+
+bit_grid_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+bit_grid_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+bit_grid_op!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Wide enough that rows span three words.
+    const COLS: usize = 150;
+
+    fn sample() -> BitGrid {
+        let mut grid = BitGrid::new(3, COLS);
+        for (r, c) in [(0, 0), (0, 63), (0, 64), (1, 62), (1, 127), (1, 128), (2, 149)] {
+            grid.set(r, c, true);
+        }
+        grid
+    }
+
+    fn ones(grid: &BitGrid) -> Vec<(usize, usize)> {
+        grid.iter_ones().collect()
+    }
+
+    #[test]
+    fn shift_cols_across_words() {
+        let grid = sample();
+        assert_eq!(ones(&grid.shift_cols(1)), vec![(0, 1), (0, 64), (0, 65), (1, 63), (1, 128), (1, 129)]);
+        assert_eq!(ones(&grid.shift_cols(-1)), vec![(0, 62), (0, 63), (1, 61), (1, 126), (1, 127), (2, 148)]);
+        assert_eq!(ones(&grid.shift_cols(65)), vec![(0, 65), (0, 128), (0, 129), (1, 127)]);
+        assert_eq!(ones(&grid.shift_cols(-65)), vec![(1, 62), (1, 63), (2, 84)]);
+        assert!(!grid.shift_cols(COLS as isize).any());
+        assert_eq!(grid.shift_cols(0), grid);
+
+        // Nothing lands in the unused bits past the last column.
+        assert_eq!(grid.shift_cols(3).count_ones(), 6);
+        assert_eq!(grid.shift_cols(3), !&!&grid.shift_cols(3));
+
+        let wrapped = grid.shift_cols_wrapping(1);
+        assert_eq!(ones(&wrapped), vec![(0, 1), (0, 64), (0, 65), (1, 63), (1, 128), (1, 129), (2, 0)]);
+        assert_eq!(grid.shift_cols_wrapping(-(COLS as isize) - 1), grid.shift_cols_wrapping(-1));
+        assert_eq!(wrapped.shift_cols_wrapping(-1), grid);
+    }
+
+    #[test]
+    fn shift_rows() {
+        let grid = sample();
+        let down = grid.shift_rows(1);
+        assert_eq!(ones(&down), vec![(1, 0), (1, 63), (1, 64), (2, 62), (2, 127), (2, 128)]);
+        assert_eq!(ones(&grid.shift_rows(-2)), vec![(0, 149)]);
+        assert!(!grid.shift_rows(5).any());
+        assert_eq!(grid.shift_rows_wrapping(4), grid.shift_rows_wrapping(1));
+        assert_eq!(grid.shift_rows_wrapping(1).shift_rows_wrapping(-1), grid);
+    }
+
+    #[test]
+    fn map_windows3_matches_window3() {
+        // Conway's rule, checked cell by cell against window3 with either outside value,
+        // with and without a margin.
+        let life = |index: usize| {
+            let alive = index & 0b000_010_000 != 0;
+            let n = (index & !0b000_010_000).count_ones();
+            n == 3 || (alive && n == 2)
+        };
+        let grid = sample();
+        for outside in [false, true] {
+            for margin in [0, 1, 2] {
+                let next = grid.map_windows3(margin, outside, life);
+                assert_eq!((next.rows, next.cols), (3 + 2 * margin, COLS + 2 * margin));
+                for r in 0..next.rows {
+                    for c in 0..next.cols {
+                        let (gr, gc) = (r.wrapping_sub(margin), c.wrapping_sub(margin));
+                        assert_eq!(next.get(r, c), life(grid.window3(gr, gc, outside)),
+                            "({}, {}) with margin {} and outside {}", r, c, margin, outside);
+                    }
+                }
+            }
+        }
+
+        // A blinker flips between a row and a column.
+        let mut blinker = BitGrid::new(5, 5);
+        for c in 1..4 {
+            blinker.set(2, c, true);
+        }
+        let flipped = blinker.map_windows3(0, false, life);
+        assert_eq!(ones(&flipped), vec![(1, 2), (2, 2), (3, 2)]);
+        assert_eq!(flipped.map_windows3(0, false, life), blinker);
+    }
+}
@@ -0,0 +1,181 @@
+// Axis-aligned boxes in any number of dimensions, and sets of them.
+//
+// A box is one Interval per axis, so it's half-open along each of them.
+
+use super::Interval;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid<const N: usize> {
+    pub ranges: [Interval; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(ranges: [Interval; N]) -> Self {
+        Cuboid{ranges}
+    }
+
+    // Includes both corners.
+    pub fn inclusive(first: [i32; N], last: [i32; N]) -> Self {
+        Cuboid{ranges: std::array::from_fn(|axis| Interval::inclusive(first[axis], last[axis]))}
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.iter().any(|range| range.is_empty())
+    }
+
+    pub fn volume(&self) -> usize {
+        self.ranges.iter().map(|range| range.len()).product()
+    }
+
+    #[allow(dead_code)]
+    pub fn contains_point(&self, pt: [i32; N]) -> bool {
+        self.ranges.iter().zip(pt).all(|(range, x)| range.contains(x))
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, other: &Cuboid<N>) -> bool {
+        other.is_empty() || self.ranges.iter().zip(&other.ranges).all(|(a, b)| a.contains_interval(*b))
+    }
+
+    pub fn intersect(&self, other: &Cuboid<N>) -> Cuboid<N> {
+        Cuboid{ranges: std::array::from_fn(|axis| self.ranges[axis].intersect(other.ranges[axis]))}
+    }
+
+    pub fn overlaps(&self, other: &Cuboid<N>) -> bool {
+        self.ranges.iter().zip(&other.ranges).all(|(a, b)| a.overlaps(*b))
+    }
+
+    // Disjoint boxes covering what's in self but not other (at most two per axis).
+    pub fn subtract(&self, other: &Cuboid<N>) -> Vec<Cuboid<N>> {
+        if !self.overlaps(other) {
+            return if self.is_empty() { Vec::new() } else { vec![*self] };
+        }
+        // Slices off whatever sticks out either side of other, one axis at a time, and
+        // narrows down to the overlap as it goes.
+        let mut pieces = Vec::with_capacity(2 * N);
+        let mut rest = *self;
+        for axis in 0..N {
+            let (below, above) = rest.ranges[axis].difference(other.ranges[axis]);
+            for side in [below, above] {
+                if !side.is_empty() {
+                    let mut piece = rest;
+                    piece.ranges[axis] = side;
+                    pieces.push(piece);
+                }
+            }
+            rest.ranges[axis] = rest.ranges[axis].intersect(other.ranges[axis]);
+        }
+        pieces
+    }
+}
+
+// A region built by switching boxes on and off, kept as disjoint boxes.
+#[derive(Debug, Default, Clone)]
+pub struct CuboidSet<const N: usize> {
+    boxes: Vec<Cuboid<N>>,
+}
+
+impl<const N: usize> CuboidSet<N> {
+    pub fn new() -> Self {
+        CuboidSet{boxes: Vec::new()}
+    }
+
+    pub fn boxes(&self) -> &[Cuboid<N>] {
+        &self.boxes
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    pub fn volume(&self) -> usize {
+        self.boxes.iter().map(|b| b.volume()).sum()
+    }
+
+    // The volume of the part inside `region`.
+    pub fn volume_within(&self, region: &Cuboid<N>) -> usize {
+        self.boxes.iter().map(|b| b.intersect(region).volume()).sum()
+    }
+
+    #[allow(dead_code)]
+    pub fn contains_point(&self, pt: [i32; N]) -> bool {
+        self.boxes.iter().any(|b| b.contains_point(pt))
+    }
+
+    pub fn turn_on(&mut self, added: Cuboid<N>) {
+        self.turn_off(added);
+        if !added.is_empty() {
+            self.boxes.push(added);
+        }
+    }
+
+    pub fn turn_off(&mut self, removed: Cuboid<N>) {
+        if removed.is_empty() {
+            return;
+        }
+        let mut kept = Vec::with_capacity(self.boxes.len());
+        for b in &self.boxes {
+            if b.overlaps(&removed) {
+                kept.extend(b.subtract(&removed));
+            } else {
+                kept.push(*b);
+            }
+        }
+        self.boxes = kept;
+    }
+
+    pub fn set(&mut self, region: Cuboid<N>, on: bool) {
+        if on { self.turn_on(region) } else { self.turn_off(region) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cube(first: [i32; 3], last: [i32; 3]) -> Cuboid<3> {
+        Cuboid::inclusive(first, last)
+    }
+
+    #[test]
+    fn subtract() {
+        let outer = cube([0, 0, 0], [9, 9, 9]);
+        let hole = cube([3, 3, 3], [5, 5, 5]);
+        let pieces = outer.subtract(&hole);
+        assert_eq!(pieces.len(), 6);
+        assert_eq!(pieces.iter().map(|p| p.volume()).sum::<usize>(), 1000 - 27);
+        for (i, a) in pieces.iter().enumerate() {
+            assert!(!a.overlaps(&hole));
+            assert!(outer.contains(a));
+            assert!(pieces[i + 1..].iter().all(|b| !a.overlaps(b)));
+        }
+
+        // Off a corner, all of it, and none of it.
+        let corner = outer.subtract(&cube([8, 8, 8], [20, 20, 20]));
+        assert_eq!(corner.iter().map(|p| p.volume()).sum::<usize>(), 1000 - 8);
+        assert!(hole.subtract(&outer).is_empty());
+        assert_eq!(hole.subtract(&cube([6, 0, 0], [9, 9, 9])), vec![hole]);
+        assert!(cube([1, 1, 1], [0, 0, 0]).subtract(&hole).is_empty());
+    }
+
+    #[test]
+    fn cuboid_set() {
+        let mut set = CuboidSet::new();
+        set.turn_on(cube([10, 10, 10], [12, 12, 12]));
+        set.turn_on(cube([11, 11, 11], [13, 13, 13]));
+        assert_eq!(set.volume(), 27 + 19);
+        set.turn_off(cube([9, 9, 9], [11, 11, 11]));
+        assert_eq!(set.volume(), 27 + 19 - 8);
+        set.turn_on(cube([10, 10, 10], [10, 10, 10]));
+        assert_eq!(set.volume(), 39);
+        assert!(set.contains_point([10, 10, 10]) && !set.contains_point([11, 10, 10]));
+
+        // Only the corner of the second box reaches into this region.
+        assert_eq!(set.volume_within(&cube([12, 12, 12], [20, 20, 20])), 8);
+        set.set(cube([0, 0, 0], [20, 20, 20]), false);
+        assert!(set.is_empty());
+        set.turn_on(cube([1, 1, 1], [0, 0, 0]));
+        assert!(set.is_empty());
+    }
+}
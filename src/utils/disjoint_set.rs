@@ -0,0 +1,171 @@
+// Union-find over the elements 0..n, with union by rank and path compression.
+
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    // Only up to date for roots.
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl DisjointSet {
+    // Every element starts in its own component.
+    pub fn new(n: usize) -> Self {
+        DisjointSet{parent: (0..n).collect(), rank: vec![0; n], size: vec![1; n], components: n}
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    // Adds a new element in its own component, and returns it.
+    pub fn add(&mut self) -> usize {
+        let x = self.parent.len();
+        self.parent.push(x);
+        self.rank.push(0);
+        self.size.push(1);
+        self.components += 1;
+        x
+    }
+
+    // The representative of x's component.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // Points everything on the way straight at the root.
+        let mut at = x;
+        while self.parent[at] != root {
+            let next = self.parent[at];
+            self.parent[at] = root;
+            at = next;
+        }
+        root
+    }
+
+    // Merges the components of a and b. Returns false if they were already the same.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (big, small) = if self.rank[a] >= self.rank[b] { (a, b) } else { (b, a) };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+        if self.rank[big] == self.rank[small] {
+            self.rank[big] += 1;
+        }
+        self.components -= 1;
+        true
+    }
+
+    // The number of elements in x's component.
+    #[allow(dead_code)]
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    pub fn num_components(&self) -> usize {
+        self.components
+    }
+
+    // The representative of every component, in increasing order.
+    #[allow(dead_code)]
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.parent.len()).filter(|x| self.parent[*x] == *x)
+    }
+
+    // The elements of every component, grouped and sorted, ordered by their smallest element.
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut group_of = vec![usize::MAX; self.len()];
+        let mut groups = Vec::<Vec<usize>>::with_capacity(self.components);
+        for x in 0..self.len() {
+            let root = self.find(x);
+            if group_of[root] == usize::MAX {
+                group_of[root] = groups.len();
+                groups.push(Vec::with_capacity(self.size[root]));
+            }
+            groups[group_of[root]].push(x);
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn union_reports_whether_it_merged() {
+        let mut set = DisjointSet::new(4);
+        assert!(set.union(0, 1));
+        assert!(set.union(2, 1));
+        assert!(!set.union(0, 2));
+        assert!(!set.union(3, 3));
+        assert_eq!(set.num_components(), 2);
+    }
+
+    #[test]
+    fn find_is_stable_under_compression() {
+        let mut set = DisjointSet::new(8);
+        // Pairs, then pairs of pairs, then the two halves, for a tree three levels deep.
+        for (a, b) in [(0, 1), (2, 3), (4, 5), (6, 7), (0, 2), (4, 6), (0, 4)] {
+            set.union(a, b);
+        }
+        let root = set.find(0);
+        assert!((0..8).all(|x| set.find(x) == root));
+        // Everything now points straight at the root, and still finds it.
+        assert!((0..8).all(|x| set.parent[x] == root));
+        assert!((0..8).all(|x| set.find(x) == root));
+    }
+
+    #[test]
+    fn sizes_after_chained_unions() {
+        let mut set = DisjointSet::new(6);
+        set.union(0, 1);
+        set.union(1, 2);
+        set.union(2, 3);
+        assert_eq!(set.size_of(0), 4);
+        assert_eq!(set.size_of(3), 4);
+        assert_eq!(set.size_of(4), 1);
+        set.union(4, 5);
+        set.union(5, 0);
+        assert!((0..6).all(|x| set.size_of(x) == 6));
+    }
+
+    #[test]
+    fn add_grows_the_set() {
+        let mut set = DisjointSet::new(0);
+        assert!(set.is_empty());
+        assert_eq!((set.add(), set.add(), set.add()), (0, 1, 2));
+        assert_eq!((set.len(), set.num_components()), (3, 3));
+        set.union(0, 2);
+        let x = set.add();
+        assert_eq!((x, set.len(), set.num_components(), set.size_of(x)), (3, 4, 3, 1));
+        set.union(x, 0);
+        assert_eq!(set.size_of(2), 3);
+    }
+
+    #[test]
+    fn a_known_partition() {
+        let mut set = DisjointSet::new(7);
+        // {0, 3, 5}, {1, 6}, {2}, {4}
+        for (a, b) in [(5, 3), (0, 5), (6, 1)] {
+            set.union(a, b);
+        }
+        assert_eq!(set.num_components(), 4);
+        assert_eq!(set.components(), vec![vec![0, 3, 5], vec![1, 6], vec![2], vec![4]]);
+        let roots: Vec<usize> = set.roots().collect();
+        assert_eq!(roots.len(), 4);
+        let mut root_of_each: Vec<usize> = [0, 1, 2, 4].into_iter().map(|x| set.find(x)).collect();
+        root_of_each.sort_unstable();
+        assert_eq!(roots, root_of_each);
+    }
+}
@@ -0,0 +1,235 @@
+// A dense 3D grid, indexed by (i, j, k) with k varying fastest.
+//
+// Slicing along an axis gives a plain Grid of the other two axes (in order), so the 2D
+// helpers like fmt_map work on any cross-section.
+
+use std::ops::{Index, IndexMut};
+
+use super::{Grid, Pt3};
+
+// Neighbor offsets as (di, dj, dk), in index order.
+#[allow(dead_code)]
+const OFFSETS6: [(isize, isize, isize); 6] = [(-1, 0, 0), (0, -1, 0), (0, 0, -1), (0, 0, 1), (0, 1, 0), (1, 0, 0)];
+
+#[derive(Clone, Debug)]
+pub struct Grid3<T> {
+    pub dim1: usize,
+    pub dim2: usize,
+    pub dim3: usize,
+    pub data: Vec<T>,
+}
+
+impl<T> Grid3<T> {
+    pub fn filled(dim1: usize, dim2: usize, dim3: usize, value: T) -> Self
+        where T: Clone
+    {
+        Self{dim1, dim2, dim3, data: vec![value; dim1 * dim2 * dim3]}
+    }
+
+    #[inline]
+    pub fn loc(&self, i: usize, j: usize, k: usize) -> usize {
+        i * (self.dim2 * self.dim3) + j * self.dim3 + k
+    }
+
+    #[allow(dead_code)]
+    pub fn dims(&self) -> (usize, usize, usize) {
+        (self.dim1, self.dim2, self.dim3)
+    }
+
+    #[inline]
+    #[allow(dead_code)]
+    pub fn contains(&self, i: usize, j: usize, k: usize) -> bool {
+        i < self.dim1 && j < self.dim2 && k < self.dim3
+    }
+
+    #[inline]
+    #[allow(dead_code)]
+    pub fn get(&self, i: usize, j: usize, k: usize) -> Option<&T> {
+        if self.contains(i, j, k) { Some(&self.data[self.loc(i, j, k)]) } else { None }
+    }
+
+    #[inline]
+    #[allow(dead_code)]
+    pub fn get_mut(&mut self, i: usize, j: usize, k: usize) -> Option<&mut T> {
+        if self.contains(i, j, k) {
+            let idx = self.loc(i, j, k);
+            Some(&mut self.data[idx])
+        } else {
+            None
+        }
+    }
+
+    // Every (i, j, k), in storage order.
+    #[allow(dead_code)]
+    pub fn indices(&self) -> impl Iterator<Item = (usize, usize, usize)> {
+        let (dim1, dim2, dim3) = self.dims();
+        (0..dim1).flat_map(move |i| (0..dim2).flat_map(move |j| (0..dim3).map(move |k| (i, j, k))))
+    }
+
+    #[allow(dead_code)]
+    pub fn iter_indexed(&self) -> impl Iterator<Item = ((usize, usize, usize), &T)> {
+        self.indices().zip(self.data.iter())
+    }
+
+    // Face neighbors. Like Grid's, these don't borrow the grid.
+    #[allow(dead_code)]
+    pub fn adjacent6(&self, i: usize, j: usize, k: usize) -> impl Iterator<Item = (usize, usize, usize)> {
+        let (dim1, dim2, dim3) = self.dims();
+        OFFSETS6.into_iter().filter_map(move |(di, dj, dk)| {
+            let (ni, nj, nk) = (i.wrapping_add_signed(di), j.wrapping_add_signed(dj), k.wrapping_add_signed(dk));
+            if ni < dim1 && nj < dim2 && nk < dim3 { Some((ni, nj, nk)) } else { None }
+        })
+    }
+
+    // Face, edge and corner neighbors.
+    #[allow(dead_code)]
+    pub fn adjacent26(&self, i: usize, j: usize, k: usize) -> impl Iterator<Item = (usize, usize, usize)> {
+        let (dim1, dim2, dim3) = self.dims();
+        (0..27).filter(|&n| n != 13).filter_map(move |n| {
+            let (ni, nj, nk) = ((i + n / 9).wrapping_sub(1), (j + n / 3 % 3).wrapping_sub(1), (k + n % 3).wrapping_sub(1));
+            if ni < dim1 && nj < dim2 && nk < dim3 { Some((ni, nj, nk)) } else { None }
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn neighbors6(&self, i: usize, j: usize, k: usize) -> impl Iterator<Item = ((usize, usize, usize), &T)> {
+        self.adjacent6(i, j, k).map(move |ijk| (ijk, &self[ijk]))
+    }
+
+    #[allow(dead_code)]
+    pub fn neighbors26(&self, i: usize, j: usize, k: usize) -> impl Iterator<Item = ((usize, usize, usize), &T)> {
+        self.adjacent26(i, j, k).map(move |ijk| (ijk, &self[ijk]))
+    }
+
+    // The cross-section at `at` along `axis` (0, 1 or 2), with the remaining axes as rows
+    // and columns.
+    pub fn slice(&self, axis: usize, at: usize) -> Grid<T>
+        where T: Clone
+    {
+        let (rows, cols) = match axis {
+            0 => (self.dim2, self.dim3),
+            1 => (self.dim1, self.dim3),
+            2 => (self.dim1, self.dim2),
+            _ => panic!("No axis {}", axis),
+        };
+        let mut data = Vec::with_capacity(rows * cols);
+        for r in 0..rows {
+            for c in 0..cols {
+                let ijk = match axis {
+                    0 => (at, r, c),
+                    1 => (r, at, c),
+                    _ => (r, c, at),
+                };
+                data.push(self[ijk].clone());
+            }
+        }
+        Grid{rows, cols, data}
+    }
+
+    // Draws a cross-section, one character per cell.
+    #[allow(dead_code)]
+    pub fn fmt_slice(&self, axis: usize, at: usize, to_char: impl Fn(&T) -> char) -> String
+        where T: Clone
+    {
+        let slice = self.slice(axis, at);
+        let mut out = String::with_capacity(slice.rows * (slice.cols + 1));
+        for r in 0..slice.rows {
+            out.extend(slice.row(r).iter().map(&to_char));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl Grid3<u8> {
+    #[allow(dead_code)]
+    pub fn fmt_map(&self, axis: usize, at: usize) -> String {
+        self.slice(axis, at).fmt_map()
+    }
+}
+
+impl<T> Index<(usize, usize, usize)> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, pt: (usize, usize, usize)) -> &Self::Output {
+        &self.data[self.loc(pt.0, pt.1, pt.2)]
+    }
+}
+
+impl<T> IndexMut<(usize, usize, usize)> for Grid3<T> {
+    fn index_mut(&mut self, pt: (usize, usize, usize)) -> &mut Self::Output {
+        let idx = self.loc(pt.0, pt.1, pt.2);
+        &mut self.data[idx]
+    }
+}
+
+impl<T> Index<&Pt3> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, pt: &Pt3) -> &Self::Output {
+        &self.data[self.loc(pt.x as usize, pt.y as usize, pt.z as usize)]
+    }
+}
+
+impl<T> IndexMut<&Pt3> for Grid3<T> {
+    fn index_mut(&mut self, pt: &Pt3) -> &mut Self::Output {
+        let idx = self.loc(pt.x as usize, pt.y as usize, pt.z as usize);
+        &mut self.data[idx]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Each cell holds its own index, ijk as a three digit number.
+    fn numbered(dim1: usize, dim2: usize, dim3: usize) -> Grid3<usize> {
+        let mut grid = Grid3::filled(dim1, dim2, dim3, 0);
+        for (i, j, k) in grid.indices().collect::<Vec<_>>() {
+            grid[(i, j, k)] = 100 * i + 10 * j + k;
+        }
+        grid
+    }
+
+    #[test]
+    fn adjacent26_counts() {
+        let grid = Grid3::filled(3, 3, 3, ());
+        assert_eq!(grid.adjacent26(0, 0, 0).count(), 7);
+        assert_eq!(grid.adjacent26(2, 0, 2).count(), 7);
+        // The middle of an edge, and of a face.
+        assert_eq!(grid.adjacent26(0, 1, 0).count(), 11);
+        assert_eq!(grid.adjacent26(1, 1, 2).count(), 17);
+        assert_eq!(grid.adjacent26(1, 1, 1).count(), 26);
+        assert!(grid.adjacent26(1, 1, 1).all(|ijk| ijk != (1, 1, 1)));
+    }
+
+    #[test]
+    fn adjacent6_clips() {
+        let grid = numbered(2, 3, 4);
+        assert_eq!(grid.adjacent6(0, 0, 0).collect::<Vec<_>>(), vec![(0, 0, 1), (0, 1, 0), (1, 0, 0)]);
+        assert_eq!(grid.adjacent6(1, 2, 3).collect::<Vec<_>>(), vec![(0, 2, 3), (1, 1, 3), (1, 2, 2)]);
+        assert_eq!(grid.adjacent6(1, 1, 1).count(), 5);
+        let values: Vec<usize> = grid.neighbors6(0, 1, 2).map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![2, 11, 13, 22, 112]);
+    }
+
+    #[test]
+    fn slices() {
+        let grid = numbered(2, 3, 4);
+        let across_i = grid.slice(0, 1);
+        assert_eq!((across_i.rows, across_i.cols), (3, 4));
+        assert_eq!(across_i.row(2), &[120, 121, 122, 123]);
+        let across_j = grid.slice(1, 2);
+        assert_eq!((across_j.rows, across_j.cols), (2, 4));
+        assert_eq!(across_j.data, vec![20, 21, 22, 23, 120, 121, 122, 123]);
+        let across_k = grid.slice(2, 3);
+        assert_eq!((across_k.rows, across_k.cols), (2, 3));
+        assert_eq!(across_k.data, vec![3, 13, 23, 103, 113, 123]);
+    }
+
+    #[test]
+    #[should_panic(expected = "No axis 3")]
+    fn slice_needs_an_axis() {
+        numbered(2, 3, 4).slice(3, 0);
+    }
+}
@@ -0,0 +1,317 @@
+// Integer ranges, and sets of them.
+//
+// Intervals are half-open, [lo, hi), like Rust's `lo..hi`. Puzzles mostly give inclusive
+// ranges, so `inclusive` and `last` convert at the edges.
+
+use std::cmp::{max, min};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    pub lo: i32,
+    pub hi: i32,
+}
+
+impl Interval {
+    pub const EMPTY: Interval = Interval{lo: 0, hi: 0};
+
+    #[allow(dead_code)]
+    pub fn new(lo: i32, hi: i32) -> Self {
+        Interval{lo, hi}
+    }
+
+    // `last` can't be i32::MAX, since hi would be one past it.
+    pub fn inclusive(first: i32, last: i32) -> Self {
+        debug_assert!(last < i32::MAX, "Interval::inclusive: {} has no end past it", last);
+        Interval{lo: first, hi: last + 1}
+    }
+
+    // Everything from a to b inclusive, whichever way round they are.
+    pub fn spanning(a: i32, b: i32) -> Self {
+        Interval::inclusive(min(a, b), max(a, b))
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.hi <= self.lo
+    }
+
+    pub fn len(self) -> usize {
+        if self.is_empty() { 0 } else { (self.hi as i64 - self.lo as i64) as usize }
+    }
+
+    // The last value inside. Meaningless for an empty interval.
+    pub fn last(self) -> i32 {
+        self.hi - 1
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(self, x: i32) -> bool {
+        self.lo <= x && x < self.hi
+    }
+
+    #[allow(dead_code)]
+    pub fn contains_interval(self, other: Interval) -> bool {
+        other.is_empty() || (self.lo <= other.lo && other.hi <= self.hi)
+    }
+
+    pub fn overlaps(self, other: Interval) -> bool {
+        !self.intersect(other).is_empty()
+    }
+
+    pub fn intersect(self, other: Interval) -> Interval {
+        Interval{lo: max(self.lo, other.lo), hi: min(self.hi, other.hi)}
+    }
+
+    // The smallest interval covering both.
+    pub fn hull(self, other: Interval) -> Interval {
+        if self.is_empty() {
+            other
+        } else if other.is_empty() {
+            self
+        } else {
+            Interval{lo: min(self.lo, other.lo), hi: max(self.hi, other.hi)}
+        }
+    }
+
+    // What's left of self either side of other. Either can be empty.
+    pub fn difference(self, other: Interval) -> (Interval, Interval) {
+        if other.is_empty() {
+            return (self, Interval::EMPTY);
+        }
+        (Interval{lo: self.lo, hi: min(self.hi, other.lo)}, Interval{lo: max(self.lo, other.hi), hi: self.hi})
+    }
+
+    pub fn iter(self) -> std::ops::Range<i32> {
+        self.lo..self.hi
+    }
+}
+
+impl IntoIterator for Interval {
+    type Item = i32;
+    type IntoIter = std::ops::Range<i32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl From<std::ops::Range<i32>> for Interval {
+    fn from(range: std::ops::Range<i32>) -> Self {
+        Interval{lo: range.start, hi: range.end}
+    }
+}
+
+impl From<std::ops::RangeInclusive<i32>> for Interval {
+    fn from(range: std::ops::RangeInclusive<i32>) -> Self {
+        Interval::inclusive(*range.start(), *range.end())
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {})", self.lo, self.hi)
+    }
+}
+
+// Sorted, non-empty intervals, with anything touching merged together.
+//
+// No day needs a set of ranges yet (day 22 works with whole boxes), so only the tests use it.
+#[allow(dead_code)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+#[allow(dead_code)]
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet{intervals: Vec::new()}
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    // The number of values covered.
+    pub fn len(&self) -> usize {
+        self.intervals.iter().map(|iv| iv.len()).sum()
+    }
+
+    // The smallest interval covering everything.
+    pub fn span(&self) -> Interval {
+        match (self.intervals.first(), self.intervals.last()) {
+            (Some(first), Some(last)) => Interval{lo: first.lo, hi: last.hi},
+            _ => Interval::EMPTY,
+        }
+    }
+
+    pub fn contains(&self, x: i32) -> bool {
+        // The first interval that ends after x is the only one that can hold it.
+        let idx = self.intervals.partition_point(|iv| iv.hi <= x);
+        self.intervals.get(idx).is_some_and(|iv| iv.contains(x))
+    }
+
+    pub fn insert(&mut self, mut added: Interval) {
+        if added.is_empty() {
+            return;
+        }
+        // Everything in [start, end) touches the new interval and gets merged into it.
+        let start = self.intervals.partition_point(|iv| iv.hi < added.lo);
+        let end = self.intervals.partition_point(|iv| iv.lo <= added.hi);
+        for iv in &self.intervals[start..end] {
+            added = added.hull(*iv);
+        }
+        self.intervals.splice(start..end, [added]);
+    }
+
+    pub fn remove(&mut self, removed: Interval) {
+        if removed.is_empty() {
+            return;
+        }
+        let start = self.intervals.partition_point(|iv| iv.hi <= removed.lo);
+        let end = self.intervals.partition_point(|iv| iv.lo < removed.hi);
+        let mut kept = Vec::with_capacity(2);
+        if start < end {
+            let (below, _) = self.intervals[start].difference(removed);
+            let (_, above) = self.intervals[end - 1].difference(removed);
+            kept.extend([below, above].into_iter().filter(|iv| !iv.is_empty()));
+        }
+        self.intervals.splice(start..end, kept);
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = self.clone();
+        for iv in &other.intervals {
+            out.insert(*iv);
+        }
+        out
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            let both = a.intersect(b);
+            if !both.is_empty() {
+                intervals.push(both);
+            }
+            // Whichever ends first can't overlap anything further on.
+            if a.hi <= b.hi { i += 1; } else { j += 1; }
+        }
+        IntervalSet{intervals}
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = self.clone();
+        for iv in &other.intervals {
+            out.remove(*iv);
+        }
+        out
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = i32> + '_ {
+        self.intervals.iter().flat_map(|iv| iv.iter())
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for iv in iter {
+            set.insert(iv);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(ranges: &[(i32, i32)]) -> IntervalSet {
+        ranges.iter().map(|&(lo, hi)| Interval::new(lo, hi)).collect()
+    }
+
+    fn pairs(set: &IntervalSet) -> Vec<(i32, i32)> {
+        set.intervals().iter().map(|iv| (iv.lo, iv.hi)).collect()
+    }
+
+    #[test]
+    fn inclusive_near_the_limits() {
+        let top = Interval::inclusive(i32::MAX - 1, i32::MAX - 1);
+        assert_eq!((top.len(), top.last()), (1, i32::MAX - 1));
+        assert!(top.contains(i32::MAX - 1) && !top.contains(i32::MAX));
+        let all = Interval::spanning(i32::MAX - 1, i32::MIN);
+        assert_eq!((all.lo, all.len()), (i32::MIN, u32::MAX as usize));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "has no end past it")]
+    fn inclusive_up_to_max() {
+        Interval::inclusive(0, i32::MAX);
+    }
+
+    #[test]
+    fn insert_merges() {
+        let mut s = set(&[(10, 20), (0, 5), (30, 40)]);
+        assert_eq!(pairs(&s), vec![(0, 5), (10, 20), (30, 40)]);
+
+        // Adjacent intervals merge, ones a value apart don't.
+        s.insert(Interval::new(5, 7));
+        s.insert(Interval::new(21, 29));
+        assert_eq!(pairs(&s), vec![(0, 7), (10, 20), (21, 29), (30, 40)]);
+        s.insert(Interval::new(20, 21));
+        assert_eq!(pairs(&s), vec![(0, 7), (10, 29), (30, 40)]);
+
+        // Swallowing several at once.
+        s.insert(Interval::new(6, 35));
+        assert_eq!(pairs(&s), vec![(0, 40)]);
+        assert_eq!(s.len(), 40);
+        assert_eq!(s.span(), Interval::new(0, 40));
+    }
+
+    #[test]
+    fn remove_splits() {
+        let mut s = set(&[(0, 10), (20, 30)]);
+        s.remove(Interval::new(3, 5));
+        assert_eq!(pairs(&s), vec![(0, 3), (5, 10), (20, 30)]);
+        s.remove(Interval::new(8, 25));
+        assert_eq!(pairs(&s), vec![(0, 3), (5, 8), (25, 30)]);
+        // Removing right up to an edge, or next to one, leaves no empty pieces.
+        s.remove(Interval::new(0, 3));
+        s.remove(Interval::new(30, 40));
+        assert_eq!(pairs(&s), vec![(5, 8), (25, 30)]);
+        s.remove(Interval::new(-100, 100));
+        assert!(s.is_empty());
+        assert_eq!(s.span(), Interval::EMPTY);
+    }
+
+    #[test]
+    fn empty_intervals() {
+        let mut s = set(&[(5, 5), (7, 3)]);
+        assert!(s.is_empty());
+        s.insert(Interval::new(0, 10));
+        s.insert(Interval::new(20, 20));
+        s.remove(Interval::new(4, 4));
+        s.remove(Interval::new(8, 2));
+        assert_eq!(pairs(&s), vec![(0, 10)]);
+        assert_eq!(Interval::EMPTY.hull(Interval::new(3, 4)), Interval::new(3, 4));
+        assert_eq!(Interval::new(3, 3).len(), 0);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25)]);
+        assert_eq!(pairs(&a.union(&b)), vec![(0, 30)]);
+        assert_eq!(pairs(&a.intersection(&b)), vec![(5, 10), (20, 25)]);
+        assert_eq!(pairs(&a.difference(&b)), vec![(0, 5), (25, 30)]);
+        assert!(a.contains(0) && a.contains(9) && !a.contains(10) && !a.contains(19) && a.contains(20));
+        assert_eq!(set(&[(1, 3), (4, 5)]).iter().collect::<Vec<_>>(), vec![1, 2, 4]);
+    }
+}
@@ -0,0 +1,158 @@
+// Extra iterator adapters and consumers, available on every iterator.
+
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+// Yields each run of N consecutive items, like slice::windows but for any iterator.
+pub struct Windows<I: Iterator, const N: usize> {
+    iter: I,
+    // Filled on construction, or None if there weren't N items.
+    window: Option<[I::Item; N]>,
+    first: bool,
+}
+
+impl<I: Iterator, const N: usize> Iterator for Windows<I, N> where I::Item: Clone {
+    type Item = [I::Item; N];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let window = self.window.as_mut()?;
+        if self.first {
+            self.first = false;
+        } else {
+            let item = self.iter.next()?;
+            // Shuffles the oldest item up to the end, where it's replaced. (Much faster
+            // than rotate_left for tiny arrays.)
+            for i in 1..N {
+                window.swap(i - 1, i);
+            }
+            window[N - 1] = item;
+        }
+        Some(window.clone())
+    }
+}
+
+pub trait IterExt: Iterator + Sized {
+    // The smallest and largest items, in one pass.
+    fn minmax(mut self) -> Option<(Self::Item, Self::Item)>
+        where Self::Item: Ord + Clone
+    {
+        let first = self.next()?;
+        Some(self.fold((first.clone(), first), |(lo, hi), item| {
+            if item < lo {
+                (item, hi)
+            } else if item > hi {
+                (lo, item)
+            } else {
+                (lo, hi)
+            }
+        }))
+    }
+
+    // How many times each item appears.
+    fn counts(self) -> FxHashMap<Self::Item, usize>
+        where Self::Item: Eq + Hash
+    {
+        let mut counts = FxHashMap::default();
+        for item in self {
+            *counts.entry(item).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // How many times each index in 0..N appears.
+    fn counts_array<const N: usize>(self) -> [usize; N]
+        where Self: Iterator<Item = usize>
+    {
+        let mut counts = [0; N];
+        for idx in self {
+            counts[idx] += 1;
+        }
+        counts
+    }
+
+    fn windows<const N: usize>(mut self) -> Windows<Self, N>
+        where Self::Item: Clone
+    {
+        assert!(N > 0, "Windows must hold at least one item");
+        let first: Vec<Self::Item> = self.by_ref().take(N).collect();
+        Windows{iter: self, window: first.try_into().ok(), first: true}
+    }
+
+    // The position of the first smallest item.
+    #[allow(dead_code)]
+    fn argmin(self) -> Option<usize>
+        where Self::Item: Ord
+    {
+        self.enumerate().min_by(|(i, a), (j, b)| a.cmp(b).then(i.cmp(j))).map(|(i, _)| i)
+    }
+
+    // The position of the first largest item.
+    #[allow(dead_code)]
+    fn argmax(self) -> Option<usize>
+        where Self::Item: Ord
+    {
+        self.enumerate().max_by(|(i, a), (j, b)| a.cmp(b).then(j.cmp(i))).map(|(i, _)| i)
+    }
+
+    // A stable sort, so items with equal keys keep their order.
+    fn sorted_by_key<K: Ord>(self, key: impl FnMut(&Self::Item) -> K) -> Vec<Self::Item> {
+        let mut items: Vec<_> = self.collect();
+        items.sort_by_key(key);
+        items
+    }
+}
+
+impl<I: Iterator> IterExt for I {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn windows_on_short_iterators() {
+        assert_eq!([1, 2, 3].into_iter().windows::<2>().collect::<Vec<_>>(), vec![[1, 2], [2, 3]]);
+        assert_eq!([1, 2, 3].into_iter().windows::<3>().collect::<Vec<_>>(), vec![[1, 2, 3]]);
+        assert_eq!([1, 2, 3].into_iter().windows::<4>().count(), 0);
+        assert_eq!([7].into_iter().windows::<1>().collect::<Vec<_>>(), vec![[7]]);
+        assert_eq!(std::iter::empty::<i32>().windows::<1>().count(), 0);
+        assert_eq!("ab".bytes().windows::<2>().collect::<Vec<_>>(), vec![*b"ab"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one item")]
+    fn empty_windows() {
+        [1, 2].into_iter().windows::<0>().count();
+    }
+
+    #[test]
+    fn minmax_on_short_iterators() {
+        assert_eq!(std::iter::empty::<i32>().minmax(), None);
+        assert_eq!([5].into_iter().minmax(), Some((5, 5)));
+        assert_eq!([5, 2].into_iter().minmax(), Some((2, 5)));
+        assert_eq!([2, 5].into_iter().minmax(), Some((2, 5)));
+        assert_eq!([3, 3, 3].into_iter().minmax(), Some((3, 3)));
+        assert_eq!([4, 9, -1, 9, 0].into_iter().minmax(), Some((-1, 9)));
+    }
+
+    #[test]
+    fn argmin_and_argmax() {
+        assert_eq!(std::iter::empty::<i32>().argmin(), None);
+        assert_eq!(std::iter::empty::<i32>().argmax(), None);
+        assert_eq!([7].into_iter().argmin(), Some(0));
+        // Ties go to the first.
+        assert_eq!([3, 1, 4, 1, 5, 9, 2, 9].into_iter().argmin(), Some(1));
+        assert_eq!([3, 1, 4, 1, 5, 9, 2, 9].into_iter().argmax(), Some(5));
+        assert_eq!([2, 2, 2].into_iter().argmin(), Some(0));
+        assert_eq!([2, 2, 2].into_iter().argmax(), Some(0));
+    }
+
+    #[test]
+    fn counts() {
+        let counts = "abca".bytes().counts();
+        assert_eq!((counts[&b'a'], counts[&b'b'], counts.len()), (2, 1, 3));
+        assert_eq!([0, 2, 2, 3].into_iter().counts_array::<4>(), [1, 0, 2, 1]);
+        assert_eq!(["bb", "a", "cc", "d"].into_iter().sorted_by_key(|s| s.len()), vec!["a", "d", "bb", "cc"]);
+    }
+}
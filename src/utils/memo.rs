@@ -0,0 +1,125 @@
+// Memoization for recursive solvers.
+//
+// The recursive function takes the Memo as its first argument and wraps its body in
+// get_or_compute, which hands the Memo back for the recursive calls:
+//
+//     fn ways(memo: &mut Memo<u32, usize>, n: u32) -> usize {
+//         memo.get_or_compute(n, |memo| if n < 2 { 1 } else { ways(memo, n - 1) + ways(memo, n - 2) })
+//     }
+
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use rustc_hash::FxHashMap;
+
+pub trait MemoCache<K, V> {
+    fn get(&self, key: &K) -> Option<V>;
+    fn insert(&mut self, key: K, value: V);
+}
+
+// For any hashable key.
+pub struct HashCache<K, V>(FxHashMap<K, V>);
+
+impl<K, V> Default for HashCache<K, V> {
+    fn default() -> Self {
+        HashCache(FxHashMap::default())
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> MemoCache<K, V> for HashCache<K, V> {
+    fn get(&self, key: &K) -> Option<V> {
+        self.0.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.0.insert(key, value);
+    }
+}
+
+// For keys that map onto 0..len, without any hashing.
+pub struct DenseCache<K, V, F> {
+    slots: Vec<Option<V>>,
+    index_of: F,
+    key: PhantomData<K>,
+}
+
+impl<K, V: Clone, F: Fn(&K) -> usize> DenseCache<K, V, F> {
+    pub fn new(len: usize, index_of: F) -> Self {
+        DenseCache{slots: vec![None; len], index_of, key: PhantomData}
+    }
+}
+
+impl<K, V: Clone, F: Fn(&K) -> usize> MemoCache<K, V> for DenseCache<K, V, F> {
+    fn get(&self, key: &K) -> Option<V> {
+        self.slots[(self.index_of)(key)].clone()
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.slots[(self.index_of)(&key)] = Some(value);
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+pub struct Memo<K, V, C = HashCache<K, V>> {
+    cache: C,
+    pub stats: MemoStats,
+    kv: PhantomData<(K, V)>,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo::with_cache(HashCache::default())
+    }
+}
+
+impl<K, V: Clone, C: MemoCache<K, V>> Memo<K, V, C> {
+    pub fn with_cache(cache: C) -> Self {
+        Memo{cache, stats: MemoStats::default(), kv: PhantomData}
+    }
+
+    // The cached value for key, or else compute(self), which is cached for next time.
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value;
+        }
+        self.stats.misses += 1;
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ways<C: MemoCache<u32, usize>>(memo: &mut Memo<u32, usize, C>, n: u32) -> usize {
+        memo.get_or_compute(n, |memo| if n < 2 { 1 } else { ways(memo, n - 1) + ways(memo, n - 2) })
+    }
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let mut memo = Memo::new();
+        assert_eq!(ways(&mut memo, 30), 1346269);
+        // Every n from 0 to 30 is computed once, and every n from 1 to 28 is found again
+        // by the call that computes n + 2 (0 is first needed there).
+        assert_eq!((memo.stats.hits, memo.stats.misses), (28, 31));
+        assert_eq!(ways(&mut memo, 30), 1346269);
+        assert_eq!((memo.stats.hits, memo.stats.misses), (29, 31));
+    }
+
+    #[test]
+    fn dense_cache() {
+        let mut memo = Memo::with_cache(DenseCache::new(31, |n: &u32| *n as usize));
+        assert_eq!(ways(&mut memo, 30), 1346269);
+        assert_eq!((memo.stats.hits, memo.stats.misses), (28, 31));
+        assert_eq!(ways(&mut memo, 10), 89);
+        assert_eq!((memo.stats.hits, memo.stats.misses), (29, 31));
+    }
+}
@@ -0,0 +1,147 @@
+// Reads the block letters that some puzzles draw instead of printing an answer.
+//
+// The letters are 4 cells wide and 6 tall, one column apart:
+//
+//   .##..###.
+//   #..#.#..#
+//   #..#.###.
+//   ####.#..#
+//   #..#.#..#
+//   #..#.###.
+
+use super::{Grid, Pt};
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+const GLYPH_STRIDE: usize = GLYPH_WIDTH + 1;
+
+// Each glyph is its 6 rows of 4 cells, row by row.
+const GLYPHS: &[(char, &str)] = &[
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+pub struct Ocr {
+    // Unrecognized glyphs show up as '?'.
+    pub text: String,
+    // (position in text, the glyph drawn with '#' and '.')
+    pub unrecognized: Vec<(usize, String)>,
+}
+
+fn read_glyphs(rows: usize, cols: usize, lit: impl Fn(usize, usize) -> bool) -> Ocr {
+    let mut text = String::new();
+    let mut unrecognized = Vec::new();
+    let num_glyphs = (cols + 1) / GLYPH_STRIDE;
+    for k in 0..num_glyphs {
+        let mut cells = String::with_capacity(GLYPH_WIDTH * GLYPH_HEIGHT);
+        for r in 0..GLYPH_HEIGHT {
+            for c in 0..GLYPH_WIDTH {
+                let on = r < rows && lit(r, k * GLYPH_STRIDE + c);
+                cells.push(if on { '#' } else { '.' });
+            }
+        }
+        match GLYPHS.iter().find(|(_, glyph)| *glyph == cells) {
+            Some((letter, _)) => text.push(*letter),
+            None => {
+                let drawn = cells.as_bytes().chunks(GLYPH_WIDTH)
+                    .map(|row| String::from_utf8_lossy(row).into_owned())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                unrecognized.push((k, drawn));
+                text.push('?');
+            }
+        }
+    }
+    Ocr{text, unrecognized}
+}
+
+// Any cell other than 0, '.' or ' ' counts as lit. Letters start at the top left corner.
+#[allow(dead_code)]
+pub fn ocr_grid(grid: &Grid<u8>) -> Ocr {
+    read_glyphs(grid.rows, grid.cols, |r, c| {
+        c < grid.cols && !matches!(grid[(r, c)], 0 | b'.' | b' ')
+    })
+}
+
+// Every letter has dots in its top row and left column, so the top left dot anchors the text.
+pub fn ocr_pts<'a>(pts: impl IntoIterator<Item = &'a Pt>) -> Ocr {
+    let pts: Vec<&Pt> = pts.into_iter().collect();
+    if pts.is_empty() {
+        return Ocr{text: String::new(), unrecognized: Vec::new()};
+    }
+    let min_x = pts.iter().map(|pt| pt.x).min().unwrap();
+    let min_y = pts.iter().map(|pt| pt.y).min().unwrap();
+    let cols = (pts.iter().map(|pt| pt.x).max().unwrap() - min_x + 1) as usize;
+    let rows = (pts.iter().map(|pt| pt.y).max().unwrap() - min_y + 1) as usize;
+
+    let mut grid = Grid::filled(rows, cols, 0u8);
+    for pt in pts {
+        grid[((pt.y - min_y) as usize, (pt.x - min_x) as usize)] = b'#';
+    }
+    read_glyphs(rows, cols, |r, c| c < cols && grid[(r, c)] != 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HELLO: &str = "\
+#..#.####.#....#.....##.
+#..#.#....#....#....#..#
+####.###..#....#....#..#
+#..#.#....#....#....#..#
+#..#.#....#....#....#..#
+#..#.####.####.####..##.
+";
+
+    #[test]
+    fn reads_a_grid() {
+        // The last letter's trailing column is missing, as it is in the puzzles.
+        let grid = Grid::parse(HELLO, |ch| ch).unwrap();
+        let ocr = ocr_grid(&grid);
+        assert_eq!(ocr.text, "HELLO");
+        assert!(ocr.unrecognized.is_empty());
+    }
+
+    #[test]
+    fn reads_points_anywhere() {
+        let grid = Grid::parse(HELLO, |ch| ch).unwrap();
+        let pts: Vec<Pt> = (0..grid.rows).flat_map(|r| (0..grid.cols).map(move |c| (r, c)))
+            .filter(|rc| grid[*rc] == b'#')
+            .map(|(r, c)| Pt{x: c as i32 - 40, y: r as i32 + 7})
+            .collect();
+        assert_eq!(ocr_pts(&pts).text, "HELLO");
+        assert_eq!(ocr_pts(&[]).text, "");
+    }
+
+    #[test]
+    fn flags_unknown_glyphs() {
+        // There's no T in the puzzles' alphabet.
+        let grid = Grid::parse("\
+#..#.###.
+#..#..#..
+####..#..
+#..#..#..
+#..#..#..
+#..#..#..
+", |ch| ch).unwrap();
+        let ocr = ocr_grid(&grid);
+        assert_eq!(ocr.text, "H?");
+        assert_eq!(ocr.unrecognized.len(), 1);
+        assert_eq!(ocr.unrecognized[0], (1, "###.\n.#..\n.#..\n.#..\n.#..\n.#..".to_string()));
+    }
+}
@@ -0,0 +1,106 @@
+// Reads character maps (one line per row) into grids.
+
+use std::fmt;
+
+use super::Grid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridParseError {
+    // Line numbers are 1-based, lengths exclude the newline.
+    Ragged{line: usize, len: usize, expected: usize},
+    BlankLine{line: usize},
+}
+
+impl fmt::Display for GridParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridParseError::Ragged{line, len, expected} =>
+                write!(f, "line {} is {} wide, expected {}", line, len, expected),
+            GridParseError::BlankLine{line} => write!(f, "blank line {} inside the grid", line),
+        }
+    }
+}
+
+impl std::error::Error for GridParseError {}
+
+// Splits into lines without the "\n" or "\r\n", dropping any blank lines at the end.
+fn map_lines(input: &str) -> impl Iterator<Item = (usize, &[u8])> {
+    let input = input.trim_end_matches(['\n', '\r']);
+    let lines = if input.is_empty() { None } else { Some(input.as_bytes().split(|b| *b == b'\n')) };
+    lines.into_iter().flatten()
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+}
+
+impl<T> Grid<T> {
+    // Blank lines before the map are skipped, but every byte of every line after that is a
+    // cell. Maps where lines are indented or end early want parse_padded instead.
+    pub fn parse(input: &str, mut cell: impl FnMut(u8) -> T) -> Result<Grid<T>, GridParseError> {
+        let mut data = Vec::with_capacity(input.len());
+        let mut rows = 0;
+        let mut cols = 0;
+        let lines = map_lines(input).skip_while(|(_, line)| line.iter().all(|b| b.is_ascii_whitespace()));
+        for (line_no, line) in lines {
+            if line.is_empty() {
+                return Err(GridParseError::BlankLine{line: line_no});
+            }
+            if rows == 0 {
+                cols = line.len();
+            } else if line.len() != cols {
+                return Err(GridParseError::Ragged{line: line_no, len: line.len(), expected: cols});
+            }
+            data.extend(line.iter().map(|b| cell(*b)));
+            rows += 1;
+        }
+        Ok(Grid{rows, cols, data})
+    }
+
+    // For maps where leading spaces are part of the picture and lines end early: short
+    // lines are padded out to the widest one with `fill`.
+    pub fn parse_padded(input: &str, fill: T, mut cell: impl FnMut(u8) -> T) -> Grid<T>
+        where T: Clone
+    {
+        let lines: Vec<&[u8]> = map_lines(input).map(|(_, line)| line).collect();
+        let cols = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let mut data = Vec::with_capacity(lines.len() * cols);
+        for line in &lines {
+            data.extend(line.iter().map(|b| cell(*b)));
+            data.resize(data.len() + cols - line.len(), fill.clone());
+        }
+        Grid{rows: lines.len(), cols, data}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_crlf() {
+        let grid = Grid::parse("12\r\n34\r\n", |ch| ch - b'0').unwrap();
+        assert_eq!((grid.rows, grid.cols), (2, 2));
+        assert_eq!(grid.data, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn rejects_ragged_and_blank() {
+        assert_eq!(Grid::parse("123\n45\n", |ch| ch).err(),
+            Some(GridParseError::Ragged{line: 2, len: 2, expected: 3}));
+        assert_eq!(Grid::parse("12\r\n345\r\n", |ch| ch).err(),
+            Some(GridParseError::Ragged{line: 2, len: 3, expected: 2}));
+        assert_eq!(Grid::parse("12\n\n34\n", |ch| ch).err(), Some(GridParseError::BlankLine{line: 2}));
+    }
+
+    #[test]
+    fn keeps_leading_spaces() {
+        // Only the blank lines before the map go, not the spaces inside it.
+        let grid = Grid::parse("\n\n #\n# \n", |ch| ch).unwrap();
+        assert_eq!((grid.rows, grid.cols), (2, 2));
+        assert_eq!(grid.data, b" ## ".to_vec());
+
+        let padded = Grid::parse_padded("  #\n#\n", b'.', |ch| ch);
+        assert_eq!((padded.rows, padded.cols), (2, 3));
+        assert_eq!(padded.data, b"  ##..".to_vec());
+    }
+}
@@ -0,0 +1,127 @@
+// Dependency-free PGM/PPM (binary "P5"/"P6") output, for turning grids into images.
+//
+// Frame sequences can be stitched together with e.g.
+//   convert -delay 5 frames/day25_*.ppm day25.gif
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::Grid;
+
+pub type Rgb = [u8; 3];
+
+fn write_pnm<T>(path: &Path, grid: &Grid<T>, scale: usize, magic: &str, pixel: impl Fn(&T, &mut Vec<u8>)) -> io::Result<()> {
+    let scale = scale.max(1);
+    let mut out = BufWriter::new(File::create(path)?);
+    write!(out, "{}\n{} {}\n255\n", magic, grid.cols * scale, grid.rows * scale)?;
+
+    let mut row_bytes = Vec::with_capacity(grid.cols * scale * 3);
+    for r in 0..grid.rows {
+        row_bytes.clear();
+        for c in 0..grid.cols {
+            for _ in 0..scale {
+                pixel(&grid[(r, c)], &mut row_bytes);
+            }
+        }
+        for _ in 0..scale {
+            out.write_all(&row_bytes)?;
+        }
+    }
+    out.flush()
+}
+
+// Grayscale, one byte per cell. Each cell becomes a scale x scale block.
+pub fn write_pgm(path: impl AsRef<Path>, grid: &Grid<u8>, scale: usize) -> io::Result<()> {
+    write_pnm(path.as_ref(), grid, scale, "P5", |v, out| out.push(*v))
+}
+
+pub fn write_ppm(path: impl AsRef<Path>, grid: &Grid<Rgb>, scale: usize) -> io::Result<()> {
+    write_pnm(path.as_ref(), grid, scale, "P6", |v, out| out.extend_from_slice(v))
+}
+
+// Writes numbered images (prefix_0000.pgm, prefix_0001.pgm, ...) into a directory.
+pub struct FrameWriter {
+    dir: PathBuf,
+    prefix: String,
+    scale: usize,
+    next: usize,
+}
+
+impl FrameWriter {
+    pub fn new(dir: impl AsRef<Path>, prefix: &str, scale: usize) -> io::Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(FrameWriter{dir: dir.as_ref().to_owned(), prefix: prefix.to_string(), scale, next: 0})
+    }
+
+    fn next_path(&mut self, ext: &str) -> PathBuf {
+        let path = self.dir.join(format!("{}_{:04}.{}", self.prefix, self.next, ext));
+        self.next += 1;
+        path
+    }
+
+    pub fn pgm(&mut self, grid: &Grid<u8>) -> io::Result<PathBuf> {
+        let path = self.next_path("pgm");
+        write_pgm(&path, grid, self.scale)?;
+        Ok(path)
+    }
+
+    pub fn ppm(&mut self, grid: &Grid<Rgb>) -> io::Result<PathBuf> {
+        let path = self.next_path("ppm");
+        write_ppm(&path, grid, self.scale)?;
+        Ok(path)
+    }
+
+    pub fn frames_written(&self) -> usize {
+        self.next
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A scratch directory per test, so tests running at once don't collide.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("advent21_pnm_{}_{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn pgm_at_scale() {
+        let path = scratch("pgm").join("out.pgm");
+        let grid = Grid{rows: 2, cols: 3, data: vec![0, 1, 2, 10, 11, 12]};
+        write_pgm(&path, &grid, 2).unwrap();
+        let mut expected = b"P5\n6 4\n255\n".to_vec();
+        for row in [[0, 0, 1, 1, 2, 2], [0, 0, 1, 1, 2, 2], [10, 10, 11, 11, 12, 12], [10, 10, 11, 11, 12, 12]] {
+            expected.extend(row);
+        }
+        assert_eq!(std::fs::read(&path).unwrap(), expected);
+
+        // Scale 0 is treated as 1.
+        write_pgm(&path, &grid, 0).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), [b"P5\n3 2\n255\n".as_slice(), &[0, 1, 2, 10, 11, 12]].concat());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn ppm_at_scale() {
+        let path = scratch("ppm").join("out.ppm");
+        let grid = Grid{rows: 1, cols: 2, data: vec![[1, 2, 3], [4, 5, 6]]};
+        write_ppm(&path, &grid, 3).unwrap();
+        let row = [[1, 2, 3]; 3].into_iter().chain([[4, 5, 6]; 3]).flatten().collect::<Vec<u8>>();
+        assert_eq!(std::fs::read(&path).unwrap(), [b"P6\n6 3\n255\n".to_vec(), row.clone(), row.clone(), row].concat());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn numbered_frames() {
+        let dir = scratch("frames");
+        let mut frames = FrameWriter::new(&dir, "day99", 1).unwrap();
+        assert_eq!(frames.pgm(&Grid{rows: 1, cols: 1, data: vec![7]}).unwrap(), dir.join("day99_0000.pgm"));
+        assert_eq!(frames.ppm(&Grid{rows: 1, cols: 1, data: vec![[7, 8, 9]]}).unwrap(), dir.join("day99_0001.ppm"));
+        assert_eq!(frames.frames_written(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
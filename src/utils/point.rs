@@ -0,0 +1,192 @@
+// Integer points/vectors in 2D and 3D.
+//
+// Both types get the same API from one macro, but keep their named fields so `p.x` and
+// `Pt{x, y}` work as usual. Ordering is lexicographic by field (x first).
+
+macro_rules! point_type {
+    ($name:ident, $dims:literal, $($f:ident),+) => {
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name {
+            $(pub $f: i32),+
+        }
+
+        impl $name {
+            pub const ZERO: $name = $name{$($f: 0),+};
+
+            pub fn manhattan(self, other: $name) -> i32 {
+                0 $(+ (self.$f - other.$f).abs())+
+            }
+
+            pub fn dist_sqr(self, other: $name) -> i32 {
+                0 $(+ (self.$f - other.$f).pow(2))+
+            }
+
+            pub fn dist(self, other: $name) -> f32 {
+                (self.dist_sqr(other) as f32).sqrt()
+            }
+
+            pub fn dot(self, other: $name) -> i32 {
+                0 $(+ self.$f * other.$f)+
+            }
+
+            pub fn abs(self) -> $name {
+                $name{$($f: self.$f.abs()),+}
+            }
+
+            pub fn signum(self) -> $name {
+                $name{$($f: self.$f.signum()),+}
+            }
+
+            // Per component, unlike Ord::min/max.
+            pub fn min_each(self, other: $name) -> $name {
+                $name{$($f: self.$f.min(other.$f)),+}
+            }
+
+            pub fn max_each(self, other: $name) -> $name {
+                $name{$($f: self.$f.max(other.$f)),+}
+            }
+
+            pub fn to_array(self) -> [i32; $dims] {
+                [$(self.$f),+]
+            }
+        }
+
+        impl From<[i32; $dims]> for $name {
+            fn from(a: [i32; $dims]) -> Self {
+                let [$($f),+] = a;
+                $name{$($f),+}
+            }
+        }
+
+        impl From<$name> for [i32; $dims] {
+            fn from(p: $name) -> Self {
+                p.to_array()
+            }
+        }
+
+        impl std::ops::Add for $name {
+            type Output = $name;
+
+            fn add(self, rhs: $name) -> $name {
+                $name{$($f: self.$f + rhs.$f),+}
+            }
+        }
+
+        impl std::ops::Sub for $name {
+            type Output = $name;
+
+            fn sub(self, rhs: $name) -> $name {
+                $name{$($f: self.$f - rhs.$f),+}
+            }
+        }
+
+        impl std::ops::Add for &$name {
+            type Output = $name;
+
+            fn add(self, rhs: &$name) -> $name {
+                *self + *rhs
+            }
+        }
+
+        impl std::ops::Sub for &$name {
+            type Output = $name;
+
+            fn sub(self, rhs: &$name) -> $name {
+                *self - *rhs
+            }
+        }
+
+        impl std::ops::AddAssign for $name {
+            fn add_assign(&mut self, rhs: $name) {
+                $(self.$f += rhs.$f;)+
+            }
+        }
+
+        impl std::ops::SubAssign for $name {
+            fn sub_assign(&mut self, rhs: $name) {
+                $(self.$f -= rhs.$f;)+
+            }
+        }
+
+        impl std::ops::Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name{$($f: -self.$f),+}
+            }
+        }
+
+        impl std::ops::Mul<i32> for $name {
+            type Output = $name;
+
+            fn mul(self, k: i32) -> $name {
+                $name{$($f: self.$f * k),+}
+            }
+        }
+
+        impl std::ops::Mul<$name> for i32 {
+            type Output = $name;
+
+            fn mul(self, p: $name) -> $name {
+                p * self
+            }
+        }
+
+        impl std::ops::MulAssign<i32> for $name {
+            fn mul_assign(&mut self, k: i32) {
+                $(self.$f *= k;)+
+            }
+        }
+    };
+}
+
+point_type!(Pt, 2, x, y);
+point_type!(Pt3, 3, x, y, z);
+
+impl Pt {
+    pub fn at(x: i32, y: i32) -> Pt {
+        Pt{x, y}
+    }
+}
+
+impl Pt3 {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self{x, y, z}
+    }
+}
+
+impl From<(i32, i32)> for Pt {
+    fn from((x, y): (i32, i32)) -> Self {
+        Pt{x, y}
+    }
+}
+
+impl From<Pt> for (i32, i32) {
+    fn from(p: Pt) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl From<(i32, i32, i32)> for Pt3 {
+    fn from((x, y, z): (i32, i32, i32)) -> Self {
+        Pt3{x, y, z}
+    }
+}
+
+impl From<Pt3> for (i32, i32, i32) {
+    fn from(p: Pt3) -> Self {
+        (p.x, p.y, p.z)
+    }
+}
+
+impl std::fmt::Display for Pt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}, {}>", self.x, self.y)
+    }
+}
+
+impl std::fmt::Display for Pt3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}, {}, {}>", self.x, self.y, self.z)
+    }
+}
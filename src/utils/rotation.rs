@@ -0,0 +1,145 @@
+// The 24 rotations that map the axes onto axes, and rigid transforms built from them.
+//
+// A rotation is stored as a signed permutation: component i of the result is
+// sign[i] * component perm[i] of the input. Only proper rotations (no mirroring) exist.
+
+use super::Pt3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rotation {
+    perm: [u8; 3],
+    sign: [i8; 3],
+}
+
+const PERMS: [[u8; 3]; 6] = [[0, 1, 2], [1, 2, 0], [2, 0, 1], [0, 2, 1], [2, 1, 0], [1, 0, 2]];
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation{perm: [0, 1, 2], sign: [1, 1, 1]};
+
+    // All 24, starting with the identity.
+    pub fn all() -> impl Iterator<Item = Rotation> {
+        PERMS.iter().enumerate().flat_map(|(k, &perm)| {
+            // The last three permutations are odd, so they need an odd number of flips.
+            let parity = if k < 3 { 1 } else { -1 };
+            (0..4).map(move |flips: u8| {
+                let (s0, s1) = (if flips & 1 == 0 { 1 } else { -1 }, if flips & 2 == 0 { 1 } else { -1 });
+                Rotation{perm, sign: [s0, s1, parity * s0 * s1]}
+            })
+        })
+    }
+
+    pub fn apply(self, p: Pt3) -> Pt3 {
+        let a = p.to_array();
+        Pt3{
+            x: self.sign[0] as i32 * a[self.perm[0] as usize],
+            y: self.sign[1] as i32 * a[self.perm[1] as usize],
+            z: self.sign[2] as i32 * a[self.perm[2] as usize],
+        }
+    }
+
+    // Applies `first`, then self.
+    pub fn compose(self, first: Rotation) -> Rotation {
+        let mut perm = [0; 3];
+        let mut sign = [0; 3];
+        for i in 0..3 {
+            let via = self.perm[i] as usize;
+            perm[i] = first.perm[via];
+            sign[i] = self.sign[i] * first.sign[via];
+        }
+        Rotation{perm, sign}
+    }
+
+    pub fn inverse(self) -> Rotation {
+        let mut perm = [0; 3];
+        let mut sign = [0; 3];
+        for i in 0..3 {
+            perm[self.perm[i] as usize] = i as u8;
+            sign[self.perm[i] as usize] = self.sign[i];
+        }
+        Rotation{perm, sign}
+    }
+
+    // The rotation taking `from` to `to`, if there's exactly one. Vectors with repeated
+    // absolute components (or two zeros) can be turned more than one way.
+    pub fn aligning(from: Pt3, to: Pt3) -> Option<Rotation> {
+        let mut found = Rotation::all().filter(|rot| rot.apply(from) == to);
+        let rot = found.next()?;
+        if found.next().is_some() { None } else { Some(rot) }
+    }
+}
+
+// p -> rot(p) + shift
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RigidTransform {
+    pub rot: Rotation,
+    pub shift: Pt3,
+}
+
+impl RigidTransform {
+    pub const IDENTITY: RigidTransform = RigidTransform{rot: Rotation::IDENTITY, shift: Pt3::ZERO};
+
+    pub fn apply(self, p: Pt3) -> Pt3 {
+        self.rot.apply(p) + self.shift
+    }
+
+    // Applies `first`, then self.
+    pub fn compose(self, first: RigidTransform) -> RigidTransform {
+        RigidTransform{rot: self.rot.compose(first.rot), shift: self.rot.apply(first.shift) + self.shift}
+    }
+
+    pub fn inverse(self) -> RigidTransform {
+        let rot = self.rot.inverse();
+        RigidTransform{rot, shift: -rot.apply(self.shift)}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn all_24_distinct() {
+        let all: Vec<Rotation> = Rotation::all().collect();
+        assert_eq!(all.len(), 24);
+        assert_eq!(all[0], Rotation::IDENTITY);
+
+        // Distinct as maps, not just as stored, and none of them mirror.
+        let probe = Pt3{x: 1, y: 2, z: 3};
+        let images: std::collections::HashSet<Pt3> = all.iter().map(|rot| rot.apply(probe)).collect();
+        assert_eq!(images.len(), 24);
+        for rot in &all {
+            let (x, y, z) = (rot.apply(Pt3{x: 1, y: 0, z: 0}), rot.apply(Pt3{x: 0, y: 1, z: 0}), rot.apply(Pt3{x: 0, y: 0, z: 1}));
+            let cross = Pt3{x: x.y * y.z - x.z * y.y, y: x.z * y.x - x.x * y.z, z: x.x * y.y - x.y * y.x};
+            assert_eq!(cross, z, "{:?} is a reflection", rot);
+        }
+    }
+
+    #[test]
+    fn compose_and_inverse() {
+        let probe = Pt3{x: 1, y: -2, z: 3};
+        for a in Rotation::all() {
+            assert_eq!(a.compose(a.inverse()), Rotation::IDENTITY);
+            assert_eq!(a.inverse().compose(a), Rotation::IDENTITY);
+            for b in Rotation::all() {
+                assert_eq!(a.compose(b).apply(probe), a.apply(b.apply(probe)));
+                assert!(Rotation::all().any(|c| c == a.compose(b)));
+            }
+        }
+
+        let t = RigidTransform{rot: Rotation::all().nth(7).unwrap(), shift: Pt3{x: 5, y: -1, z: 2}};
+        let u = RigidTransform{rot: Rotation::all().nth(19).unwrap(), shift: Pt3{x: 0, y: 3, z: -4}};
+        assert_eq!(t.inverse().apply(t.apply(probe)), probe);
+        assert_eq!(t.compose(u).apply(probe), t.apply(u.apply(probe)));
+        assert_eq!(t.compose(t.inverse()), RigidTransform::IDENTITY);
+    }
+
+    #[test]
+    fn aligning() {
+        let from = Pt3{x: 1, y: 2, z: 3};
+        for rot in Rotation::all() {
+            assert_eq!(Rotation::aligning(from, rot.apply(from)), Some(rot));
+        }
+        // (1, 1, 0) can be turned onto itself more than one way.
+        assert_eq!(Rotation::aligning(Pt3{x: 1, y: 1, z: 0}, Pt3{x: 1, y: 1, z: 0}), None);
+    }
+}
@@ -0,0 +1,195 @@
+// Pulls numbers and tokens out of puzzle input without allocating.
+//
+// Record parsers chain `int`, `tag` and friends with `?`. Each one returns None, and leaves
+// the position alone, if the input doesn't match there (`word` skips whitespace first).
+
+pub trait ScanInt: Copy {
+    const SIGNED: bool;
+    // None if the value doesn't fit.
+    fn from_digits(negative: bool, magnitude: u64) -> Option<Self>;
+}
+
+macro_rules! scan_int {
+    ($signed:literal, $($t:ty),+) => {
+        $(impl ScanInt for $t {
+            const SIGNED: bool = $signed;
+
+            #[inline]
+            fn from_digits(negative: bool, magnitude: u64) -> Option<Self> {
+                let value = if negative { -(magnitude as i128) } else { magnitude as i128 };
+                <$t>::try_from(value).ok()
+            }
+        })+
+    };
+}
+
+scan_int!(true, i8, i16, i32, i64, isize);
+scan_int!(false, u8, u16, u32, u64, usize);
+
+#[inline]
+pub fn hex_digit(ch: u8) -> Option<u8> {
+    match ch {
+        b'0'..=b'9' => Some(ch - b'0'),
+        b'a'..=b'f' => Some(ch - b'a' + 10),
+        b'A'..=b'F' => Some(ch - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ByteScanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteScanner<'a> {
+    pub fn new(input: &'a str) -> Self {
+        ByteScanner{bytes: input.as_bytes(), pos: 0}
+    }
+
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        ByteScanner{bytes, pos: 0}
+    }
+
+    pub fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.pos..]
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    pub fn byte(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    // Consumes `literal` if the input continues with it.
+    pub fn tag(&mut self, literal: &str) -> Option<()> {
+        if self.rest().starts_with(literal.as_bytes()) {
+            self.pos += literal.len();
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    // An integer right at the current position. Signed types take a leading '-'. Numbers too
+    // big for T are None, like anything else that doesn't match.
+    pub fn int<T: ScanInt>(&mut self) -> Option<T> {
+        let rest = self.rest();
+        let negative = T::SIGNED && rest.first() == Some(&b'-');
+        let start = negative as usize;
+        let digits = rest[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        let magnitude = rest[start..start + digits].iter()
+            .try_fold(0u64, |acc, b| acc.checked_mul(10)?.checked_add((b - b'0') as u64))?;
+        let value = T::from_digits(negative, magnitude)?;
+        self.pos += start + digits;
+        Some(value)
+    }
+
+    // Skips ahead to the next integer, and reads it.
+    #[allow(dead_code)]
+    pub fn next_int<T: ScanInt>(&mut self) -> Option<T> {
+        let rest = self.rest();
+        let is_start = |i: usize| rest[i].is_ascii_digit()
+            || (T::SIGNED && rest[i] == b'-' && rest.get(i + 1).is_some_and(|b| b.is_ascii_digit()));
+        let skip = (0..rest.len()).find(|i| is_start(*i))?;
+        self.pos += skip;
+        self.int()
+    }
+
+    // Every integer left in the input.
+    #[allow(dead_code)]
+    pub fn ints<T: ScanInt>(mut self) -> impl Iterator<Item = T> + 'a {
+        std::iter::from_fn(move || self.next_int())
+    }
+
+    // The next run of non-whitespace, after skipping any whitespace.
+    pub fn word(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        self.take_while(|b| !b.is_ascii_whitespace())
+    }
+
+    // The longest run of bytes matching `pred` at the current position, if it's not empty.
+    pub fn take_while(&mut self, pred: impl Fn(u8) -> bool) -> Option<&'a str> {
+        let rest = self.rest();
+        let len = rest.iter().take_while(|b| pred(**b)).count();
+        if len == 0 {
+            return None;
+        }
+        self.pos += len;
+        // Only fails if pred stops partway through a multi-byte character.
+        std::str::from_utf8(&rest[..len]).ok()
+    }
+
+    // The rest of the current line, without the line ending, which is consumed.
+    pub fn line(&mut self) -> Option<&'a str> {
+        if self.at_end() {
+            return None;
+        }
+        let rest = self.rest();
+        let len = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+        self.pos += (len + 1).min(rest.len());
+        let line = &rest[..len];
+        std::str::from_utf8(line.strip_suffix(b"\r").unwrap_or(line)).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn negatives() {
+        let mut scan = ByteScanner::new("-12,-0,7");
+        assert_eq!(scan.int::<i32>(), Some(-12));
+        scan.tag(",").unwrap();
+        assert_eq!(scan.int::<i64>(), Some(0));
+        // Unsigned types don't take the '-', and leave it where it was.
+        let mut scan = ByteScanner::new("-5");
+        assert_eq!(scan.int::<u32>(), None);
+        assert_eq!(scan.rest(), b"-5");
+        assert_eq!(ByteScanner::new("x=-3..4, y=-10..-5").ints::<i32>().collect::<Vec<_>>(), vec![-3, 4, -10, -5]);
+        assert_eq!(ByteScanner::new("x=-3..4").ints::<u8>().collect::<Vec<_>>(), vec![3, 4]);
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(ByteScanner::new("127").int::<i8>(), Some(127));
+        assert_eq!(ByteScanner::new("-128").int::<i8>(), Some(-128));
+        assert_eq!(ByteScanner::new("255").int::<u8>(), Some(255));
+        assert_eq!(ByteScanner::new("-2147483648").int::<i32>(), Some(i32::MIN));
+        assert_eq!(ByteScanner::new("-9223372036854775808").int::<i64>(), Some(i64::MIN));
+        assert_eq!(ByteScanner::new("18446744073709551615").int::<u64>(), Some(u64::MAX));
+        assert_eq!(ByteScanner::new("000000000000000000000042").int::<u8>(), Some(42));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(ByteScanner::new("128").int::<i8>(), None);
+        assert_eq!(ByteScanner::new("-129").int::<i8>(), None);
+        assert_eq!(ByteScanner::new("256").int::<u8>(), None);
+        assert_eq!(ByteScanner::new("3000000000").int::<i32>(), None);
+        assert_eq!(ByteScanner::new("9223372036854775808").int::<i64>(), None);
+        assert_eq!(ByteScanner::new("18446744073709551616").int::<u64>(), None);
+        // Past what a u64 can even accumulate.
+        let mut scan = ByteScanner::new("12345678901234567890123");
+        assert_eq!(scan.int::<u64>(), None);
+        assert_eq!(scan.rest().len(), 23);
+    }
+}
@@ -0,0 +1,296 @@
+// Best-first shortest path search (Dijkstra, or A* given a heuristic).
+//
+// `AStar` can be driven one expansion at a time (pop, then push the successors), or
+// left to `run` to completion. States are only expanded once, so the heuristic has to be
+// consistent (never overestimate, and not drop by more than a step costs).
+//
+// Only the cost is tracked unless the search is built `with_paths`, since the history
+// costs a good deal in the hot loops.
+
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+use rustc_hash::FxHashSet;
+
+use super::{ByFirstRev, Grid, Pt};
+
+// The states that have been expanded.
+pub trait Visited<S> {
+    fn contains(&self, state: &S) -> bool;
+    fn insert(&mut self, state: S);
+}
+
+// For any hashable state.
+pub type HashVisited<S> = FxHashSet<S>;
+
+impl<S: Eq + Hash> Visited<S> for FxHashSet<S> {
+    #[inline]
+    fn contains(&self, state: &S) -> bool {
+        FxHashSet::contains(self, state)
+    }
+
+    #[inline]
+    fn insert(&mut self, state: S) {
+        FxHashSet::insert(self, state);
+    }
+}
+
+// For searches over the (row, col) cells of a grid, without any hashing.
+pub struct GridVisited(Grid<bool>);
+
+impl GridVisited {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        GridVisited(Grid::filled(rows, cols, false))
+    }
+}
+
+impl Visited<(usize, usize)> for GridVisited {
+    #[inline]
+    fn contains(&self, state: &(usize, usize)) -> bool {
+        self.0[*state]
+    }
+
+    #[inline]
+    fn insert(&mut self, state: (usize, usize)) {
+        self.0[state] = true;
+    }
+}
+
+// The same cells as points, which keep the queue entries smaller.
+impl Visited<Pt> for GridVisited {
+    #[inline]
+    fn contains(&self, state: &Pt) -> bool {
+        self.0[state]
+    }
+
+    #[inline]
+    fn insert(&mut self, state: Pt) {
+        self.0[&state] = true;
+    }
+}
+
+// What a search remembers about the expanded states. Each expanded state gets a handle,
+// which its successors are queued with as their parent.
+pub trait History<S> {
+    type Handle: Copy;
+    const START: Self::Handle;
+    fn expanded(&mut self, state: &S, parent: Self::Handle) -> Self::Handle;
+    // From the start to the state, both included, or empty when nothing is recorded.
+    fn path_to(&self, handle: Self::Handle) -> Vec<S>;
+}
+
+// Records nothing, so the queue holds just (cost, state).
+impl<S> History<S> for () {
+    type Handle = ();
+    const START: () = ();
+
+    #[inline]
+    fn expanded(&mut self, _state: &S, _parent: ()) {}
+
+    fn path_to(&self, _handle: ()) -> Vec<S> {
+        Vec::new()
+    }
+}
+
+// Every expanded state with the index of its parent.
+pub struct Paths<S> {
+    expanded: Vec<(S, usize)>,
+}
+
+impl<S> Default for Paths<S> {
+    fn default() -> Self {
+        Paths{expanded: Vec::with_capacity(1024)}
+    }
+}
+
+impl<S: Clone> History<S> for Paths<S> {
+    type Handle = usize;
+    const START: usize = usize::MAX;
+
+    fn expanded(&mut self, state: &S, parent: usize) -> usize {
+        self.expanded.push((state.clone(), parent));
+        self.expanded.len() - 1
+    }
+
+    fn path_to(&self, handle: usize) -> Vec<S> {
+        let mut path = Vec::new();
+        let mut at = handle;
+        while at != Self::START {
+            let (state, parent) = &self.expanded[at];
+            path.push(state.clone());
+            at = *parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SearchStats {
+    pub pushed: usize,
+    // Including stale entries for states that were already expanded.
+    pub popped: usize,
+    pub expanded: usize,
+}
+
+pub struct Route<S> {
+    pub cost: usize,
+    // From the start to the goal, both included. Empty unless the search kept its paths.
+    pub path: Vec<S>,
+    pub stats: SearchStats,
+}
+
+// (cost + heuristic, (cost, state, parent))
+type Queued<S, P> = ByFirstRev<(usize, (usize, S, P))>;
+
+pub struct AStar<S, V = HashVisited<S>, H: History<S> = ()> {
+    queue: BinaryHeap<Queued<S, H::Handle>>,
+    visited: V,
+    history: H,
+    pub stats: SearchStats,
+}
+
+impl<S: Clone + Eq + Hash> AStar<S> {
+    pub fn new(start: S) -> Self {
+        AStar::with_visited(start, HashVisited::default())
+    }
+}
+
+impl<S: Clone, V: Visited<S>> AStar<S, V> {
+    pub fn with_visited(start: S, visited: V) -> Self {
+        let mut queue = BinaryHeap::with_capacity(1024);
+        queue.push(ByFirstRev((0, (0, start, ()))));
+        AStar{queue, visited, history: (), stats: SearchStats{pushed: 1, ..Default::default()}}
+    }
+
+    // Records every expanded state's parent, for path_to. Only before anything is popped.
+    pub fn with_paths(self) -> AStar<S, V, Paths<S>> {
+        assert_eq!(self.stats.popped, 0, "paths have to be kept from the start");
+        let queue = self.queue.into_iter()
+            .map(|ByFirstRev((priority, (cost, state, ())))| ByFirstRev((priority, (cost, state, <Paths<S> as History<S>>::START))))
+            .collect();
+        AStar{queue, visited: self.visited, history: Paths::default(), stats: self.stats}
+    }
+}
+
+impl<S: Clone, V: Visited<S>, H: History<S>> AStar<S, V, H> {
+    // Takes the cheapest state that hasn't been expanded yet, and returns it with its cost
+    // and the handle its successors should be pushed with, before popping again.
+    pub fn pop(&mut self) -> Option<(S, usize, H::Handle)> {
+        loop {
+            let ByFirstRev((_, (cost, state, parent))) = self.queue.pop()?;
+            self.stats.popped += 1;
+            if self.visited.contains(&state) {
+                continue;
+            }
+            self.visited.insert(state.clone());
+            let handle = self.history.expanded(&state, parent);
+            self.stats.expanded += 1;
+            return Some((state, cost, handle));
+        }
+    }
+
+    // Queues a successor of the expanded state `parent`, reached at a total of `cost`.
+    #[inline]
+    pub fn push(&mut self, parent: H::Handle, state: S, cost: usize, heuristic: usize) {
+        if self.visited.contains(&state) {
+            return;
+        }
+        self.stats.pushed += 1;
+        self.queue.push(ByFirstRev((cost + heuristic, (cost, state, parent))));
+    }
+
+    // The next state pop() would look at, with its cost (it may turn out to be stale).
+    pub fn peek(&self) -> Option<(&S, usize)> {
+        self.queue.peek().map(|ByFirstRev((_, (cost, state, _)))| (state, *cost))
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn path_to(&self, handle: H::Handle) -> Vec<S> {
+        self.history.path_to(handle)
+    }
+
+    // Searches until a goal is expanded. Successors come with the cost of the step to them.
+    pub fn run<I>(mut self,
+        mut successors: impl FnMut(&S) -> I,
+        mut heuristic: impl FnMut(&S) -> usize,
+        mut is_goal: impl FnMut(&S) -> bool) -> Option<Route<S>>
+        where I: IntoIterator<Item = (S, usize)>
+    {
+        while let Some((state, cost, handle)) = self.pop() {
+            if is_goal(&state) {
+                return Some(Route{cost, path: self.path_to(handle), stats: self.stats});
+            }
+            for (next, step_cost) in successors(&state) {
+                let h = heuristic(&next);
+                self.push(handle, next, cost + step_cost, h);
+            }
+        }
+        None
+    }
+}
+
+#[allow(dead_code)]
+pub fn astar<S, I>(start: S,
+    successors: impl FnMut(&S) -> I,
+    heuristic: impl FnMut(&S) -> usize,
+    is_goal: impl FnMut(&S) -> bool) -> Option<Route<S>>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item = (S, usize)>
+{
+    AStar::new(start).with_paths().run(successors, heuristic, is_goal)
+}
+
+#[allow(dead_code)]
+pub fn dijkstra<S, I>(start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool) -> Option<Route<S>>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item = (S, usize)>
+{
+    astar(start, successors, |_| 0, is_goal)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 0 -> 1 -> 3 costs 2 + 5, but 0 -> 2 -> 1 -> 3 costs 1 + 1 + 5, and 0 -> 2 -> 3 costs 1 + 7.
+    fn edges(node: &u32) -> Vec<(u32, usize)> {
+        match node {
+            0 => vec![(1, 2), (2, 1)],
+            1 => vec![(3, 5)],
+            2 => vec![(1, 1), (3, 7)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn dijkstra_finds_cheapest() {
+        let route = dijkstra(0u32, edges, |n| *n == 3).unwrap();
+        assert_eq!(route.cost, 7);
+        assert_eq!(route.path, vec![0, 1, 3]);
+        assert!(dijkstra(3u32, edges, |n| *n == 0).is_none());
+    }
+
+    #[test]
+    fn astar_on_a_grid() {
+        // A wall down the middle with a gap at the bottom.
+        let grid = Grid::parse("...\n.#.\n.#.\n...\n", |ch| ch == b'#').unwrap();
+        let goal = (0, 2);
+        let successors = |&(r, c): &(usize, usize)| grid.adjacent4(r, c).filter(|rc| !grid[*rc]).map(|rc| (rc, 1));
+        let manhattan = |&(r, c): &(usize, usize)| r.abs_diff(goal.0) + c.abs_diff(goal.1);
+
+        let route = AStar::with_visited((0, 0), GridVisited::new(grid.rows, grid.cols))
+            .run(successors, manhattan, |at| *at == goal).unwrap();
+        assert_eq!(route.cost, 2);
+        assert!(route.path.is_empty());
+
+        let route = AStar::with_visited((3, 0), GridVisited::new(grid.rows, grid.cols)).with_paths()
+            .run(successors, manhattan, |at| *at == goal).unwrap();
+        assert_eq!(route.cost, 5);
+        assert_eq!(route.path.len(), 6);
+        assert_eq!((route.path[0], route.path[5]), ((3, 0), goal));
+    }
+}
@@ -0,0 +1,193 @@
+// An unbounded 2D grid that only stores the cells that have been set.
+//
+// Every other cell reads as the background value. The bounding box is worked out from the
+// stored cells when asked for, so setting cells stays a plain hash map insert.
+
+use rustc_hash::FxHashMap;
+
+use super::{Grid, Pt};
+
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: FxHashMap<Pt, T>,
+    background: T,
+}
+
+impl<T> SparseGrid<T> {
+    #[allow(dead_code)]
+    pub fn new(background: T) -> Self {
+        SparseGrid{cells: FxHashMap::default(), background}
+    }
+
+    pub fn with_capacity(background: T, capacity: usize) -> Self {
+        SparseGrid{cells: FxHashMap::with_capacity_and_hasher(capacity, Default::default()), background}
+    }
+
+    #[allow(dead_code)]
+    pub fn background(&self) -> &T {
+        &self.background
+    }
+
+    // The number of stored cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, pt: Pt) -> bool {
+        self.cells.contains_key(&pt)
+    }
+
+    #[allow(dead_code)]
+    pub fn get(&self, pt: Pt) -> &T {
+        self.cells.get(&pt).unwrap_or(&self.background)
+    }
+
+    // Returns the previous value, if the cell was stored.
+    pub fn set(&mut self, pt: Pt, value: T) -> Option<T> {
+        self.cells.insert(pt, value)
+    }
+
+    // Stores the cell as the background first if it isn't there yet.
+    #[inline]
+    pub fn get_mut(&mut self, pt: Pt) -> &mut T
+        where T: Clone
+    {
+        self.cells.entry(pt).or_insert_with(|| self.background.clone())
+    }
+
+    #[allow(dead_code)]
+    pub fn remove(&mut self, pt: Pt) -> Option<T> {
+        self.cells.remove(&pt)
+    }
+
+    #[allow(dead_code)]
+    pub fn iter(&self) -> impl Iterator<Item = (&Pt, &T)> {
+        self.cells.iter()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &Pt> {
+        self.cells.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.values()
+    }
+
+    // Inclusive (min, max) corners of the stored cells. Goes through all of them.
+    pub fn bounds(&self) -> Option<(Pt, Pt)> {
+        let mut points = self.cells.keys();
+        let first = *points.next()?;
+        Some(points.fold((first, first), |(lo, hi), pt| (lo.min_each(*pt), hi.max_each(*pt))))
+    }
+
+    // A dense copy of the bounding box, with the min corner at (0, 0). Returns it along with
+    // that corner.
+    pub fn to_grid(&self) -> (Pt, Grid<T>)
+        where T: Clone
+    {
+        let Some((lo, hi)) = self.bounds() else {
+            return (Pt::ZERO, Grid{rows: 0, cols: 0, data: Vec::new()});
+        };
+        let (rows, cols) = ((hi.y - lo.y + 1) as usize, (hi.x - lo.x + 1) as usize);
+        let mut grid = Grid::filled(rows, cols, self.background.clone());
+        for (pt, value) in &self.cells {
+            grid[&(*pt - lo)] = value.clone();
+        }
+        (lo, grid)
+    }
+
+    // Stores the cells of a dense grid that differ from the background, with the grid's
+    // (0, 0) at `origin`.
+    #[allow(dead_code)]
+    pub fn from_grid(grid: &Grid<T>, origin: Pt, background: T) -> Self
+        where T: Clone + PartialEq
+    {
+        let mut sparse = SparseGrid::new(background);
+        for r in 0..grid.rows {
+            for c in 0..grid.cols {
+                let value = &grid[(r, c)];
+                if *value != sparse.background {
+                    sparse.set(origin + Pt::at(c as i32, r as i32), value.clone());
+                }
+            }
+        }
+        sparse
+    }
+}
+
+impl SparseGrid<u8> {
+    #[allow(dead_code)]
+    pub fn fmt_map(&self) -> String {
+        self.to_grid().1.fmt_map()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bounds() {
+        let mut grid = SparseGrid::new(0u8);
+        assert!(grid.bounds().is_none());
+        grid.set(Pt::at(-3, 2), 1);
+        assert_eq!(grid.bounds(), Some((Pt::at(-3, 2), Pt::at(-3, 2))));
+        grid.set(Pt::at(4, -5), 1);
+        grid.set(Pt::at(0, 7), 1);
+        assert_eq!(grid.bounds(), Some((Pt::at(-3, -5), Pt::at(4, 7))));
+    }
+
+    #[test]
+    fn dense_round_trip() {
+        let mut sparse = SparseGrid::new(b'.');
+        for (x, y) in [(-2, -1), (1, -1), (0, 1)] {
+            sparse.set(Pt::at(x, y), b'#');
+        }
+        let (origin, grid) = sparse.to_grid();
+        assert_eq!(origin, Pt::at(-2, -1));
+        assert_eq!((grid.rows, grid.cols), (3, 4));
+        assert_eq!(grid.data, b"#..#......#.".to_vec());
+
+        let back = SparseGrid::from_grid(&grid, origin, b'.');
+        assert_eq!(back.len(), 3);
+        let mut points: Vec<Pt> = back.points().copied().collect();
+        points.sort();
+        assert_eq!(points, vec![Pt::at(-2, -1), Pt::at(0, 1), Pt::at(1, -1)]);
+        assert_eq!(back.to_grid().0, origin);
+    }
+
+    #[test]
+    fn set_background_and_remove() {
+        let mut grid = SparseGrid::new(0u8);
+        assert!(grid.is_empty());
+        assert_eq!(grid.set(Pt::at(1, 1), 5), None);
+        assert_eq!(grid.set(Pt::at(1, 1), 7), Some(5));
+        // Setting the background value still stores the cell, so it counts for the bounds.
+        grid.set(Pt::at(-4, 0), 0);
+        assert!(grid.contains(Pt::at(-4, 0)));
+        assert_eq!((grid.len(), *grid.get(Pt::at(-4, 0))), (2, 0));
+        assert_eq!(grid.bounds(), Some((Pt::at(-4, 0), Pt::at(1, 1))));
+
+        assert_eq!(grid.remove(Pt::at(-4, 0)), Some(0));
+        assert_eq!(grid.remove(Pt::at(-4, 0)), None);
+        assert!(!grid.contains(Pt::at(-4, 0)));
+        assert_eq!(grid.bounds(), Some((Pt::at(1, 1), Pt::at(1, 1))));
+        assert_eq!(grid.remove(Pt::at(1, 1)), Some(7));
+        assert_eq!((*grid.get(Pt::at(1, 1)), grid.len()), (*grid.background(), 0));
+    }
+
+    #[test]
+    fn fmt_map() {
+        let mut grid = SparseGrid::new(0u8);
+        grid.set(Pt::at(10, -3), b'#');
+        grid.set(Pt::at(12, -2), b'#');
+        grid.set(Pt::at(11, -1), b'o');
+        assert_eq!(grid.fmt_map(), "#  \n  #\n o \n");
+    }
+}
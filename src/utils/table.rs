@@ -0,0 +1,113 @@
+// Lines up cells in right-aligned columns for the terminal, optionally colored.
+//
+// Widths are measured as displayed, so wide characters and ANSI escapes already in the
+// cells don't throw the columns off. Styles are ANSI SGR codes (like "1;33"), as for
+// animation palettes.
+
+use unicode_width::UnicodeWidthStr;
+
+use super::Grid;
+
+// The columns a string takes up in a terminal, not counting escape sequences.
+pub fn display_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut rest = s;
+    while let Some(esc) = rest.find('\x1b') {
+        width += rest[..esc].width();
+        rest = &rest[esc + 1..];
+        // A CSI sequence runs up to a final byte in @..~; anything else is just the ESC.
+        if let Some(params) = rest.strip_prefix('[') {
+            let end = params.bytes().position(|b| (0x40..=0x7e).contains(&b)).map_or(params.len(), |i| i + 1);
+            rest = &params[end..];
+        }
+    }
+    width + rest.width()
+}
+
+// The text in an SGR style, then back to plain.
+pub fn paint(text: &str, sgr: &str) -> String {
+    format!("\x1b[{}m{}\x1b[0m", sgr, text)
+}
+
+pub fn tabulate(table: &Grid<String>) -> String {
+    tabulate_styled(table, |_| None)
+}
+
+// Each cell is painted in the style given for its (row, col), if any. Padding is left plain.
+pub fn tabulate_styled<'a>(table: &Grid<String>, style: impl Fn((usize, usize)) -> Option<&'a str>) -> String {
+    let widths: Vec<usize> = table.data.iter().map(|cell| display_width(cell)).collect();
+    let mut col_widths = vec![0; table.cols];
+    for (i, width) in widths.iter().enumerate() {
+        let c = i % table.cols;
+        col_widths[c] = col_widths[c].max(*width);
+    }
+
+    let mut out = String::with_capacity(table.rows * (col_widths.iter().sum::<usize>() + table.cols));
+    for (i, cell) in table.data.iter().enumerate() {
+        let (r, c) = (i / table.cols, i % table.cols);
+        out.push_str(&" ".repeat(col_widths[c] - widths[i]));
+        match style((r, c)) {
+            Some(sgr) => out.push_str(&paint(cell, sgr)),
+            None => out.push_str(cell),
+        }
+        out.push(if c == table.cols - 1 { '\n' } else { ' ' });
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strings(rows: usize, cols: usize, cells: &[&str]) -> Grid<String> {
+        Grid{rows, cols, data: cells.iter().map(|s| s.to_string()).collect()}
+    }
+
+    #[test]
+    fn wide_characters() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width("🎄x"), 3);
+        assert_eq!(display_width(""), 0);
+    }
+
+    #[test]
+    fn combining_marks() {
+        // e followed by a combining acute accent.
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("a\u{301}\u{308}b"), 2);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(display_width(&paint("x", "1;31")), 1);
+        assert_eq!(display_width(&paint("日", "38;5;208")), 2);
+        assert_eq!(display_width("\x1b[2J\x1b[Hok"), 2);
+        // A bare ESC takes up nothing, and neither does an unfinished sequence.
+        assert_eq!(display_width("a\x1bb"), 2);
+        assert_eq!(display_width("a\x1b[1;3"), 1);
+    }
+
+    #[test]
+    fn plain_columns() {
+        let table = strings(2, 2, &["1", "22", "333", "4"]);
+        assert_eq!(tabulate(&table), "  1 22\n333  4\n");
+    }
+
+    #[test]
+    fn styled_columns_line_up() {
+        let table = strings(3, 3, &[
+            "日本", "1", "x",
+            "a", &paint("🎄", "1;32"), "e\u{301}e\u{301}",
+            "abc", "22", "",
+        ]);
+        let out = tabulate_styled(&table, |(r, c)| (r == 2 && c == 0).then_some("1;31"));
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        // Column widths are 4, 2 and 2, and every row takes up the same space.
+        assert!(lines.iter().all(|line| display_width(line) == 4 + 1 + 2 + 1 + 2));
+        assert_eq!(lines[0], "日本  1  x");
+        assert_eq!(lines[1], format!("   a {} e\u{301}e\u{301}", paint("🎄", "1;32")));
+        assert_eq!(lines[2], format!(" {} 22   ", paint("abc", "1;31")));
+    }
+}
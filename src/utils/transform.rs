@@ -0,0 +1,164 @@
+// Whole-grid reshaping. Everything here builds a new grid rather than working in place.
+
+use super::Grid;
+
+impl<T: Clone> Grid<T> {
+    // Builds a rows x cols grid where each cell is copied from self[at(r, c)].
+    fn remapped(&self, rows: usize, cols: usize, at: impl Fn(usize, usize) -> (usize, usize)) -> Grid<T> {
+        let mut data = Vec::with_capacity(rows * cols);
+        for r in 0..rows {
+            for c in 0..cols {
+                data.push(self[at(r, c)].clone());
+            }
+        }
+        Grid{rows, cols, data}
+    }
+
+    #[allow(dead_code)]
+    pub fn transpose(&self) -> Grid<T> {
+        self.remapped(self.cols, self.rows, |r, c| (c, r))
+    }
+
+    // Clockwise, so the left column becomes the top row. (`at` is only called on a
+    // non-empty grid, so the subtractions can't underflow.)
+    #[allow(dead_code)]
+    pub fn rotate_cw(&self) -> Grid<T> {
+        self.remapped(self.cols, self.rows, |r, c| (self.rows - 1 - c, r))
+    }
+
+    #[allow(dead_code)]
+    pub fn rotate_ccw(&self) -> Grid<T> {
+        self.remapped(self.cols, self.rows, |r, c| (c, self.cols - 1 - r))
+    }
+
+    // Mirrors left to right.
+    #[allow(dead_code)]
+    pub fn flip_h(&self) -> Grid<T> {
+        let mut flipped = self.clone();
+        for r in 0..self.rows {
+            flipped.row_mut(r).reverse();
+        }
+        flipped
+    }
+
+    // Mirrors top to bottom.
+    #[allow(dead_code)]
+    pub fn flip_v(&self) -> Grid<T> {
+        let mut data = Vec::with_capacity(self.data.len());
+        for r in (0..self.rows).rev() {
+            data.extend_from_slice(self.row(r));
+        }
+        Grid{rows: self.rows, cols: self.cols, data}
+    }
+
+    // The rows x cols block with its top left corner at (r, c).
+    #[allow(dead_code)]
+    pub fn subgrid(&self, r: usize, c: usize, rows: usize, cols: usize) -> Grid<T> {
+        assert!(r + rows <= self.rows && c + cols <= self.cols, "subgrid out of bounds");
+        let mut data = Vec::with_capacity(rows * cols);
+        for row in r..(r + rows) {
+            data.extend_from_slice(&self.row(row)[c..(c + cols)]);
+        }
+        Grid{rows, cols, data}
+    }
+
+    // Surrounds the grid with `margin` cells of `fill` on every side.
+    pub fn padded(&self, margin: usize, fill: T) -> Grid<T> {
+        let cols = self.cols + 2 * margin;
+        let mut data = Vec::with_capacity((self.rows + 2 * margin) * cols);
+        data.resize(margin * cols, fill.clone());
+        for r in 0..self.rows {
+            data.resize(data.len() + margin, fill.clone());
+            data.extend_from_slice(self.row(r));
+            data.resize(data.len() + margin, fill.clone());
+        }
+        data.resize(data.len() + margin * cols, fill);
+        Grid{rows: self.rows + 2 * margin, cols, data}
+    }
+
+    // Repeats the grid tiles_down x tiles_across times, passing each cell through
+    // map(tile_row, tile_col, value).
+    pub fn tiled(&self, tiles_down: usize, tiles_across: usize, map: impl Fn(usize, usize, &T) -> T) -> Grid<T> {
+        let (rows, cols) = (self.rows * tiles_down, self.cols * tiles_across);
+        let mut data = Vec::with_capacity(rows * cols);
+        for r in 0..rows {
+            let (tile_r, row) = (r / self.rows, self.row(r % self.rows));
+            for tile_c in 0..tiles_across {
+                data.extend(row.iter().map(|value| map(tile_r, tile_c, value)));
+            }
+        }
+        Grid{rows, cols, data}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 2 x 3, so the shapes show whether rows and columns were swapped.
+    fn sample() -> Grid<u8> {
+        Grid::parse("abc\ndef\n", |ch| ch).unwrap()
+    }
+
+    fn text(grid: &Grid<u8>) -> String {
+        (0..grid.rows).map(|r| String::from_utf8(grid.row(r).to_vec()).unwrap() + "\n").collect()
+    }
+
+    #[test]
+    fn rotations() {
+        let grid = sample();
+        assert_eq!(text(&grid.rotate_cw()), "da\neb\nfc\n");
+        assert_eq!(text(&grid.rotate_ccw()), "cf\nbe\nad\n");
+        let four = grid.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+        assert_eq!((four.rows, four.cols, &four.data), (grid.rows, grid.cols, &grid.data));
+        assert_eq!(grid.rotate_cw().rotate_ccw().data, grid.data);
+        assert_eq!(grid.rotate_cw().rotate_cw().data, b"fedcba".to_vec());
+    }
+
+    #[test]
+    fn transpose_and_flips() {
+        let grid = sample();
+        assert_eq!(text(&grid.transpose()), "ad\nbe\ncf\n");
+        assert_eq!(grid.transpose().transpose().data, grid.data);
+        assert_eq!(text(&grid.flip_h()), "cba\nfed\n");
+        assert_eq!(text(&grid.flip_v()), "def\nabc\n");
+        // A rotation is a transpose and a flip.
+        assert_eq!(grid.rotate_cw().data, grid.transpose().flip_h().data);
+    }
+
+    #[test]
+    fn empty_grids() {
+        let empty = Grid{rows: 0, cols: 3, data: Vec::<u8>::new()};
+        assert_eq!((empty.rotate_cw().rows, empty.rotate_cw().cols), (3, 0));
+        assert_eq!((empty.rotate_ccw().rows, empty.rotate_ccw().cols), (3, 0));
+        assert_eq!(empty.transpose().data, vec![]);
+    }
+
+    #[test]
+    fn subgrid() {
+        let grid = Grid::parse("abcd\nefgh\nijkl\n", |ch| ch).unwrap();
+        assert_eq!(text(&grid.subgrid(1, 1, 2, 3)), "fgh\njkl\n");
+        assert_eq!(text(&grid.subgrid(0, 3, 3, 1)), "d\nh\nl\n");
+        assert_eq!(grid.subgrid(2, 4, 1, 0).data, vec![]);
+        assert_eq!(grid.subgrid(0, 0, 3, 4).data, grid.data);
+    }
+
+    #[test]
+    #[should_panic(expected = "subgrid out of bounds")]
+    fn subgrid_past_the_edge() {
+        Grid::parse("abcd\nefgh\nijkl\n", |ch| ch).unwrap().subgrid(1, 2, 2, 3);
+    }
+
+    #[test]
+    fn padded_and_tiled() {
+        let grid = Grid{rows: 1, cols: 2, data: vec![1u8, 2]};
+        let padded = grid.padded(1, 0);
+        assert_eq!((padded.rows, padded.cols), (3, 4));
+        assert_eq!(padded.data, vec![0, 0, 0, 0, 0, 1, 2, 0, 0, 0, 0, 0]);
+        assert_eq!(grid.padded(0, 9).data, grid.data);
+
+        let tiled = grid.tiled(2, 3, |tile_r, tile_c, v| v + 10 * (tile_r * 3 + tile_c) as u8);
+        assert_eq!((tiled.rows, tiled.cols), (2, 6));
+        assert_eq!(tiled.data, vec![1, 2, 11, 12, 21, 22, 31, 32, 41, 42, 51, 52]);
+    }
+}